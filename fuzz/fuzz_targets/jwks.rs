@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(jwks) = auth0_rs::parse::parse_jwks_bytes(data) {
+        for key in &jwks.keys {
+            let _ = key.certificate_info();
+        }
+        let _ = auth0_rs::Auth0::from_jwks(jwks);
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use std::sync::OnceLock;
+use libfuzzer_sys::fuzz_target;
+use auth0_rs::{Auth0, ValidationOptions};
+
+fn auth0() -> &'static Auth0 {
+    static AUTH0: OnceLock<Auth0> = OnceLock::new();
+    AUTH0.get_or_init(|| {
+        let options = ValidationOptions { strict_base64: true, max_nesting_depth: 2, ..Default::default() };
+        Auth0::with_options(include_str!("../../testdata/jwks.json"), options).unwrap()
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(segments) = auth0_rs::parse::split_token(data) {
+        let _ = segments.decode_header();
+        let _ = segments.decode_payload();
+        let _ = segments.decode_signature();
+    }
+    if let Ok(token) = std::str::from_utf8(data) {
+        let _ = auth0().validate_token(token);
+        let _ = auth0().validate_token_report(token);
+    }
+});
@@ -0,0 +1,500 @@
+//! Claim assertions written in a small expression language, so authorization rules can live in
+//! configuration, e.g. a [`ValidationPolicy`](crate::policy::ValidationPolicy) document, rather
+//! than in code.
+//!
+//! An assertion is compiled once by [`ClaimsAssertion::new`] and evaluated against the claims of
+//! every validated token:
+//!
+//! ```text
+//! scope contains "read:users" && claims["https://example.com/plan"] == "enterprise"
+//! ```
+//!
+//! - Operands are string (`"..."`), number, `true`, `false` and `null` literals, lists of
+//!   operands (`["a", "b"]`) and claims. A claim is named directly (`email_verified`,
+//!   `org.id`) or through `claims`, which also reaches names that are not identifiers
+//!   (`claims["https://example.com/plan"]`); `[0]` indexes arrays. Absent claims are `null`.
+//! - `==` and `!=` compare any values, numbers by value. `<`, `<=`, `>` and `>=` compare two
+//!   numbers or two strings, and are false otherwise.
+//! - `a contains b` holds when the array `a` has an item equal to `b`, when the string `a` has
+//!   the space-delimited word `b` (as the `scope` claim does), or when the object `a` has the
+//!   key `b`. `b in a` is the same as `a contains b`.
+//! - `!`, `&&` and `||` combine conditions, with parentheses for grouping. Only `true` holds:
+//!   an assertion on a claim that is not a boolean fails.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::Claims;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Maximum nesting of parentheses, lists and operators in an assertion.
+const MAX_DEPTH: usize = 64;
+
+/// A compiled claim assertion, see the [module documentation](self) for the syntax.
+///
+/// Require assertions from every token with [`ValidationOptions::assertions`]; tokens whose
+/// claims do not satisfy one are rejected with [`ErrorKind::AssertionFailed`]. Assertions
+/// (de)serialize as their expression.
+///
+/// Example:
+/// ```
+/// use serde_json::json;
+/// use auth0_rs::assertion::ClaimsAssertion;
+/// let assertion = ClaimsAssertion::new(r#"scope contains "read:users" && org.tier >= 2"#).unwrap();
+/// assert!(assertion.evaluate(&json!({"scope": "openid read:users", "org": {"tier": 3}})));
+/// assert!(!assertion.evaluate(&json!({"scope": "read:users_app_metadata", "org": {"tier": 3}})));
+/// assert!(ClaimsAssertion::new("scope contains").is_err());
+/// ```
+///
+/// [`ValidationOptions::assertions`]: crate::ValidationOptions::assertions
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClaimsAssertion {
+    source: String,
+    expr: Expr,
+}
+
+impl ClaimsAssertion {
+    /// Compile `source`, failing with [`ErrorKind::InvalidAssertion`] if it is malformed.
+    pub fn new(source: &str) -> Result<ClaimsAssertion, Auth0Error> {
+        let expr = Parser::new(source).parse()
+            .map_err(|reason| new_error(ErrorKind::InvalidAssertion(format!("`{}`: {}", source, reason))))?;
+        Ok(ClaimsAssertion { source: source.to_string(), expr })
+    }
+
+    /// The expression the assertion was compiled from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether `claims` satisfy the assertion.
+    pub fn evaluate(&self, claims: &Claims) -> bool {
+        self.expr.evaluate(claims) == Value::Bool(true)
+    }
+
+    /// Fail with [`ErrorKind::AssertionFailed`] unless `claims` satisfy the assertion.
+    pub fn check(&self, claims: &Claims) -> Result<(), Auth0Error> {
+        if !self.evaluate(claims) {
+            return Err(new_error(ErrorKind::AssertionFailed(self.source.clone())))
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ClaimsAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ClaimsAssertion").field(&self.source).finish()
+    }
+}
+
+impl PartialEq for ClaimsAssertion {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl TryFrom<String> for ClaimsAssertion {
+    type Error = Auth0Error;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        ClaimsAssertion::new(&source)
+    }
+}
+
+impl From<ClaimsAssertion> for String {
+    fn from(assertion: ClaimsAssertion) -> Self {
+        assertion.source
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    List(Vec<Expr>),
+    Claim(Vec<Segment>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Operator, Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    In,
+}
+
+impl Expr {
+    fn evaluate(&self, claims: &Claims) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::List(items) => Value::Array(items.iter().map(|item| item.evaluate(claims)).collect()),
+            Expr::Claim(path) => {
+                let mut value = claims;
+                for segment in path {
+                    value = match (segment, value) {
+                        (Segment::Key(key), Value::Object(map)) => map.get(key),
+                        (Segment::Index(index), Value::Array(items)) => items.get(*index),
+                        _ => None,
+                    }.unwrap_or(&Value::Null);
+                }
+                value.clone()
+            }
+            Expr::Not(expr) => Value::Bool(!holds(&expr.evaluate(claims))),
+            Expr::And(left, right) => Value::Bool(holds(&left.evaluate(claims)) && holds(&right.evaluate(claims))),
+            Expr::Or(left, right) => Value::Bool(holds(&left.evaluate(claims)) || holds(&right.evaluate(claims))),
+            Expr::Compare(left, operator, right) => {
+                let (left, right) = (left.evaluate(claims), right.evaluate(claims));
+                Value::Bool(match operator {
+                    Operator::Eq => equal(&left, &right),
+                    Operator::Ne => !equal(&left, &right),
+                    Operator::Lt => compare(&left, &right) == Some(Ordering::Less),
+                    Operator::Le => matches!(compare(&left, &right), Some(Ordering::Less | Ordering::Equal)),
+                    Operator::Gt => compare(&left, &right) == Some(Ordering::Greater),
+                    Operator::Ge => matches!(compare(&left, &right), Some(Ordering::Greater | Ordering::Equal)),
+                    Operator::Contains => contains(&left, &right),
+                    Operator::In => contains(&right, &left),
+                })
+            }
+        }
+    }
+}
+
+fn holds(value: &Value) -> bool {
+    *value == Value::Bool(true)
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        (Value::Array(l), Value::Array(r)) => l.len() == r.len() && l.iter().zip(r).all(|(l, r)| equal(l, r)),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+fn contains(container: &Value, item: &Value) -> bool {
+    match (container, item) {
+        (Value::Array(items), _) => items.iter().any(|candidate| equal(candidate, item)),
+        (Value::String(words), Value::String(word)) => words.split_whitespace().any(|candidate| candidate == word),
+        (Value::Object(map), Value::String(key)) => map.contains_key(key),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Number(Value),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+/// Recursive descent parser, lowest precedence first: `||`, `&&`, `!`, comparisons, operands.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Parser {
+        Parser { tokens: vec![], position: 0, end: source.len(), depth: 0 }.tokenize(source)
+    }
+
+    fn tokenize(mut self, source: &str) -> Parser {
+        // a lexing error is recorded as an unknown symbol, reported by the parser at its offset
+        let mut chars = source.char_indices().peekable();
+        while let Some(&(offset, c)) = chars.peek() {
+            let token = match c {
+                c if c.is_whitespace() => {
+                    chars.next();
+                    continue
+                }
+                '"' => {
+                    chars.next();
+                    let mut string = String::new();
+                    let mut closed = false;
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '"' => {
+                                closed = true;
+                                break
+                            }
+                            '\\' => match chars.next() {
+                                Some((_, 'n')) => string.push('\n'),
+                                Some((_, 't')) => string.push('\t'),
+                                Some((_, c)) => string.push(c),
+                                None => break,
+                            },
+                            c => string.push(c),
+                        }
+                    }
+                    if !closed {
+                        self.tokens.push((offset, Token::Symbol("unterminated string")));
+                        break
+                    }
+                    Token::Str(string)
+                }
+                c if c.is_ascii_digit() || c == '-' => {
+                    let mut number = String::new();
+                    while let Some(&(_, c)) = chars.peek() {
+                        if !(c.is_ascii_digit() || c == '.' || (number.is_empty() && c == '-')) {
+                            break
+                        }
+                        number.push(c);
+                        chars.next();
+                    }
+                    match serde_json::from_str::<Value>(&number) {
+                        Ok(value @ Value::Number(_)) => Token::Number(value),
+                        _ => Token::Symbol("malformed number"),
+                    }
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut ident = String::new();
+                    while let Some(&(_, c)) = chars.peek() {
+                        if !(c.is_alphanumeric() || c == '_') {
+                            break
+                        }
+                        ident.push(c);
+                        chars.next();
+                    }
+                    Token::Ident(ident)
+                }
+                _ => {
+                    let rest = &source[offset..];
+                    let symbol = ["&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "(", ")", "[", "]", ",", "."]
+                        .iter().find(|symbol| rest.starts_with(**symbol)).copied();
+                    // symbols are ASCII: one char per byte
+                    for _ in 0..symbol.map_or(1, str::len) {
+                        chars.next();
+                    }
+                    Token::Symbol(symbol.unwrap_or("unexpected character"))
+                }
+            };
+            self.tokens.push((offset, token));
+        }
+        self
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.parse_or()?;
+        match self.tokens.get(self.position) {
+            None => Ok(expr),
+            Some(_) => Err(self.unexpected("end of expression")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        self.nested(|parser| {
+            let mut expr = parser.parse_and()?;
+            while parser.eat("||") {
+                expr = Expr::Or(Box::new(expr), Box::new(parser.parse_and()?));
+            }
+            Ok(expr)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_not()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return self.nested(|parser| Ok(Expr::Not(Box::new(parser.parse_not()?))))
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_operand()?;
+        let operator = match self.tokens.get(self.position).map(|(_, token)| token) {
+            Some(Token::Symbol("==")) => Operator::Eq,
+            Some(Token::Symbol("!=")) => Operator::Ne,
+            Some(Token::Symbol("<")) => Operator::Lt,
+            Some(Token::Symbol("<=")) => Operator::Le,
+            Some(Token::Symbol(">")) => Operator::Gt,
+            Some(Token::Symbol(">=")) => Operator::Ge,
+            Some(Token::Ident(ident)) if ident == "contains" => Operator::Contains,
+            Some(Token::Ident(ident)) if ident == "in" => Operator::In,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.parse_operand()?;
+        Ok(Expr::Compare(Box::new(left), operator, Box::new(right)))
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, String> {
+        let token = match self.tokens.get(self.position) {
+            Some((_, token)) => token.clone(),
+            None => return Err(self.unexpected("an operand")),
+        };
+        self.position += 1;
+        match token {
+            Token::Str(string) => Ok(Expr::Literal(Value::String(string))),
+            Token::Number(number) => Ok(Expr::Literal(number)),
+            Token::Symbol("(") => {
+                let expr = self.parse_or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Symbol("[") => self.nested(|parser| {
+                let mut items = vec![];
+                if !parser.eat("]") {
+                    loop {
+                        items.push(parser.parse_operand()?);
+                        if parser.eat("]") {
+                            break
+                        }
+                        parser.expect(",")?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }),
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "contains" | "in" => {
+                    self.position -= 1;
+                    Err(self.unexpected("an operand"))
+                }
+                "claims" => self.parse_path(vec![]),
+                _ => self.parse_path(vec![Segment::Key(ident)]),
+            },
+            Token::Symbol(_) => {
+                self.position -= 1;
+                Err(self.unexpected("an operand"))
+            }
+        }
+    }
+
+    fn parse_path(&mut self, mut path: Vec<Segment>) -> Result<Expr, String> {
+        loop {
+            if self.eat(".") {
+                match self.tokens.get(self.position) {
+                    Some((_, Token::Ident(ident))) => path.push(Segment::Key(ident.clone())),
+                    _ => return Err(self.unexpected("a claim name")),
+                }
+            } else if self.eat("[") {
+                match self.tokens.get(self.position) {
+                    Some((_, Token::Str(key))) => path.push(Segment::Key(key.clone())),
+                    Some((_, Token::Number(Value::Number(index)))) if index.as_u64().is_some() => {
+                        path.push(Segment::Index(index.as_u64().unwrap_or_default() as usize))
+                    }
+                    _ => return Err(self.unexpected("a claim name or an index")),
+                }
+                self.position += 1;
+                self.expect("]")?;
+                continue
+            } else {
+                return Ok(Expr::Claim(path))
+            }
+            self.position += 1;
+        }
+    }
+
+    fn nested<F: FnOnce(&mut Parser) -> Result<Expr, String>>(&mut self, f: F) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("nested deeper than {} levels", MAX_DEPTH))
+        }
+        let expr = f(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        match self.tokens.get(self.position) {
+            Some((_, Token::Symbol(s))) if *s == symbol => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if !self.eat(symbol) {
+            return Err(self.unexpected(&format!("`{}`", symbol)))
+        }
+        Ok(())
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.tokens.get(self.position) {
+            Some((offset, Token::Symbol(symbol))) if symbol.contains(' ') => format!("{} at offset {}", symbol, offset),
+            Some((offset, _)) => format!("expected {} at offset {}", expected, offset),
+            None => format!("expected {} at offset {}", expected, self.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{Algorithm, Auth0, ValidationOptions};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    fn test_assertions() {
+        let claims = json!({
+            "scope": "openid read:users", "permissions": ["admin"], "email_verified": true, "level": 2.0,
+            "https://x/plan": "enterprise", "org": {"id": "acme", "regions": ["eu"]},
+        });
+        let holds = |source: &str| ClaimsAssertion::new(source).unwrap().evaluate(&claims);
+        assert!(holds(r#"scope contains "read:users" && claims["https://x/plan"] == "enterprise""#));
+        assert!(!holds(r#"scope contains "read""#));
+        assert!(holds(r#""admin" in permissions && email_verified"#));
+        assert!(holds(r#"level == 2 && level >= 1.5 && !(level > 2)"#));
+        assert!(holds(r#"org.id in ["acme", "globex"] && org.regions[0] == "eu" && org contains "id""#));
+        assert!(holds(r#"missing == null || false"#));
+        assert!(!holds("scope") && !holds(r#"scope < 3"#));
+
+        for malformed in ["", "scope contains", r#"scope == "open"#, "(a", "a b", "a.", "a[-1]", "a && && b", "a # b", "[1,]"] {
+            let err = ClaimsAssertion::new(malformed).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::InvalidAssertion(_)), "{}", malformed);
+        }
+        assert!(ClaimsAssertion::new(&"(".repeat(100_000)).is_err());
+        assert!(ClaimsAssertion::new(&"!".repeat(100_000)).is_err());
+
+        let options: ValidationOptions = serde_json::from_value(json!({"assertions": [r#"sub == "auth0|a""#]})).unwrap();
+        assert_eq!(serde_json::to_value(&options.assertions).unwrap(), json!([r#"sub == "auth0|a""#]));
+        assert!(serde_json::from_value::<ValidationOptions>(json!({"assertions": ["sub =="]})).is_err());
+
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let mut auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        assert!(matches!(auth0.validate_token(&token).unwrap_err().kind(), ErrorKind::AssertionFailed(_)));
+        let report = auth0.validate_token_report(&token).unwrap_err();
+        assert_eq!(report.failures[0].check, "assertion");
+        auth0.set_options(ValidationOptions { assertions: vec![ClaimsAssertion::new("sub != null").unwrap()], ..Default::default() });
+        assert!(auth0.validate_token(&token).is_ok());
+    }
+}
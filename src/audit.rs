@@ -0,0 +1,301 @@
+//! Transcripts of validations, for compliance evidence and debugging.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::{Algorithm, CertificateExpiryPolicy, Claims, JsonWebKey, ValidationOptions};
+use crate::claims::{Audience, ClaimsExt, ClaimsExtract};
+use crate::error::Auth0Error;
+use crate::validation::{accepted_issuers, required_scopes, token_scopes};
+
+/// One step of a validation recorded in a [`ValidationTranscript`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditStep {
+    /// Name of the step: `keys`, `format`, `kid`, `key_use`, `certificate`, `alg`, `typ`,
+    /// `signature`, `exp`, `nbf`, `aud`, `iss`, `key_issuer`, `claims`, `scope`, `sid`,
+    /// `authentication`, `assertion`, `schema` or `pipeline`. Steps that do not apply under the
+    /// validation options, e.g. `aud` without an expected audience or `nbf` for tokens without
+    /// it, are not recorded.
+    pub stage: &'static str,
+    /// Whether the step passed.
+    pub passed: bool,
+    /// What the configuration expects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// What the token carries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    /// Time spent in the step.
+    pub elapsed: Duration,
+}
+
+/// Structured record of every step of a validation, see [`Auth0::set_audit_sink`](crate::Auth0::set_audit_sink)
+/// and [`Auth0::validate_token_audited`](crate::Auth0::validate_token_audited).
+///
+/// Steps are recorded in validation order, up to the first failed one; the steps of a nested
+/// token follow those of the token enclosing it. Unlike
+/// [`explain_failure`](crate::explain::explain_failure), a transcript describes the validation
+/// that actually ran and the decision it made.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ValidationTranscript {
+    /// Key ID the token was resolved to, the innermost one for a nested token.
+    pub kid: Option<String>,
+    /// Where the key was looked up: `key set`, or `watched file` while a JWKS file is watched,
+    /// followed by the issuer the key is bound to, if any.
+    pub key_source: Option<String>,
+    /// The recorded steps.
+    pub steps: Vec<AuditStep>,
+    /// Duration of the whole validation.
+    pub elapsed: Duration,
+    /// [`ErrorKind::code`](crate::error::ErrorKind::code) of the error rejecting the token, if it was rejected.
+    pub error: Option<&'static str>,
+}
+
+impl ValidationTranscript {
+    /// The failed step, if any.
+    pub fn failure(&self) -> Option<&AuditStep> {
+        self.steps.iter().find(|step| !step.passed)
+    }
+}
+
+impl fmt::Display for ValidationTranscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(kid) = &self.kid {
+            writeln!(f, "kid {} from {}", kid, self.key_source.as_deref().unwrap_or("unknown source"))?;
+        }
+        for step in &self.steps {
+            write!(f, "{} {} ({:?})", if step.passed { "ok  " } else { "FAIL" }, step.stage, step.elapsed)?;
+            if let Some(expected) = &step.expected {
+                write!(f, ": expected {}", expected)?;
+            }
+            if let Some(actual) = &step.actual {
+                write!(f, ", found {}", actual)?;
+            }
+            writeln!(f)?;
+        }
+        match self.error {
+            Some(code) => writeln!(f, "rejected: {} in {:?}", code, self.elapsed),
+            None => writeln!(f, "accepted in {:?}", self.elapsed),
+        }
+    }
+}
+
+/// Receiver of the transcripts of every validation, see [`Auth0::set_audit_sink`](crate::Auth0::set_audit_sink).
+pub type AuditSink = Arc<dyn Fn(&ValidationTranscript) + Send + Sync>;
+
+thread_local! {
+    static RECORDING: RefCell<Option<ValidationTranscript>> = const { RefCell::new(None) };
+}
+
+/// Run `validate`, recording its transcript when `enabled`.
+///
+/// When not `enabled`, a recording started by an enclosing validation is suspended, so hooks
+/// validating other tokens do not add their steps to it.
+pub(crate) fn record<T, F>(enabled: bool, validate: F) -> (Result<T, Auth0Error>, Option<ValidationTranscript>)
+    where F: FnOnce() -> Result<T, Auth0Error>
+{
+    struct Restore(Option<ValidationTranscript>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            RECORDING.with(|recording| *recording.borrow_mut() = self.0.take());
+        }
+    }
+
+    let start = Instant::now();
+    let restore = Restore(RECORDING.with(|recording| recording.replace(enabled.then(ValidationTranscript::default))));
+    let result = validate();
+    let transcript = RECORDING.with(|recording| recording.borrow_mut().take()).map(|mut transcript| {
+        transcript.elapsed = start.elapsed();
+        transcript.error = result.as_ref().err().map(|e| e.kind().code());
+        transcript
+    });
+    drop(restore);
+    (result, transcript)
+}
+
+/// Expected and actual values of a step, `None` when the step does not apply under the
+/// validation options and is not recorded.
+pub(crate) type Description = Option<(Option<String>, Option<String>)>;
+
+/// Run the `stage` check, recording it along with the values given by `describe` when a
+/// transcript is recorded.
+pub(crate) fn step<T, C, D>(stage: &'static str, check: C, describe: D) -> Result<T, Auth0Error>
+    where C: FnOnce() -> Result<T, Auth0Error>, D: FnOnce() -> Description
+{
+    if !is_recording() {
+        return check()
+    }
+    let start = Instant::now();
+    let result = check();
+    let elapsed = start.elapsed();
+    let (expected, actual) = match describe() {
+        Some(values) => values,
+        None => return result,
+    };
+    let step = AuditStep { stage, passed: result.is_ok(), expected, actual, elapsed };
+    RECORDING.with(|recording| {
+        if let Some(transcript) = recording.borrow_mut().as_mut() {
+            transcript.steps.push(step);
+        }
+    });
+    result
+}
+
+/// Record the key a token was resolved to, looked up in `source`.
+pub(crate) fn record_key(key: &JsonWebKey, source: &str) {
+    RECORDING.with(|recording| {
+        if let Some(transcript) = recording.borrow_mut().as_mut() {
+            transcript.kid = Some(key.kid.clone());
+            transcript.key_source = Some(match key.issuers().is_empty() {
+                true => source.to_string(),
+                false => format!("{} bound to {}", source, bound_issuers(key)),
+            });
+        }
+    });
+}
+
+/// Whether a transcript is being recorded on this thread.
+pub(crate) fn is_recording() -> bool {
+    RECORDING.with(|recording| recording.borrow().is_some())
+}
+
+// Descriptions of the steps, in the format of `explain_failure`.
+
+pub(crate) fn describe_format(token: &str, options: &ValidationOptions) -> Description {
+    Some((Some(format!("at most {} bytes", options.limits.max_token_bytes)), Some(format!("{} bytes", token.len()))))
+}
+
+pub(crate) fn describe_key_use(key: &JsonWebKey, options: &ValidationOptions) -> Description {
+    options.strict_key_use.then(|| (Some("sig".to_string()), Some(key.key_use.clone())))
+}
+
+pub(crate) fn describe_certificate(key: &JsonWebKey, options: &ValidationOptions) -> Description {
+    let info = key.certificate_info().and_then(Result::ok);
+    (options.certificate_expiry != CertificateExpiryPolicy::Ignore && info.is_some())
+        .then(|| (None, info.map(|info| format!("valid until {}", info.not_after))))
+}
+
+pub(crate) fn describe_algorithm(key: &JsonWebKey, actual: Algorithm, options: &ValidationOptions) -> Description {
+    let expected = if options.algorithms.is_empty() { key.alg.clone() } else { format!("one of {:?}", options.algorithms) };
+    Some((Some(expected), Some(format!("{:?}", actual))))
+}
+
+pub(crate) fn describe_token_type(actual: Option<&str>, options: &ValidationOptions) -> Description {
+    (!options.token_types.is_empty()).then(|| (Some(format!("one of {:?}", options.token_types)), actual.map(|typ| typ.to_string())))
+}
+
+pub(crate) fn describe_expiry(claims: &Claims, options: &ValidationOptions, now: u64) -> Description {
+    let earliest = now.saturating_sub(options.leeway.saturating_add(options.expired_grace.as_secs()));
+    let exp = claims.get("exp").and_then(|v| v.as_u64());
+    Some((Some(format!("at least {} (now {})", earliest, now)), exp.map(|exp| exp.to_string())))
+}
+
+pub(crate) fn describe_not_before(claims: &Claims, options: &ValidationOptions, now: u64) -> Description {
+    let nbf = claims.get("nbf")?;
+    Some((Some(format!("at most {} (now {})", now.saturating_add(options.leeway), now)), Some(nbf.to_string())))
+}
+
+pub(crate) fn describe_audience(claims: &Claims, options: &ValidationOptions) -> Description {
+    let audience = options.audience.as_ref()?;
+    let actual = claims.audience().map(Audience::into_vec).unwrap_or_default();
+    Some((Some(format!("one of {:?}", audience)), Some(format!("{:?}", actual))))
+}
+
+pub(crate) fn describe_issuer(claims: &Claims, options: &ValidationOptions) -> Description {
+    let issuers: Vec<&str> = accepted_issuers(options).collect();
+    let actual = claims.get("iss").and_then(|v| v.as_str()).map(|iss| iss.to_string());
+    (!issuers.is_empty()).then(|| (Some(issuers.join(" or ")), actual))
+}
+
+pub(crate) fn describe_key_issuer(claims: &Claims, key: &JsonWebKey) -> Description {
+    let actual = claims.get("iss").and_then(|v| v.as_str()).map(|iss| iss.to_string());
+    (!key.issuers().is_empty()).then(|| (Some(bound_issuers(key)), actual))
+}
+
+/// The issuers `key` is bound to, for display.
+fn bound_issuers(key: &JsonWebKey) -> String {
+    key.issuers().iter().map(String::as_str).collect::<Vec<_>>().join(" or ")
+}
+
+pub(crate) fn describe_required_claims(claims: &Claims, options: &ValidationOptions) -> Description {
+    let present = |name: &&String| claims.get(name.as_str()).is_some_and(|v| !v.is_null());
+    let actual: Vec<&String> = options.required_claims.iter().filter(present).collect();
+    (!options.required_claims.is_empty()).then(|| (Some(format!("{:?}", options.required_claims)), Some(format!("{:?}", actual))))
+}
+
+pub(crate) fn describe_scopes(claims: &Claims, options: &ValidationOptions) -> Description {
+    let required: Vec<&str> = required_scopes(claims, options).into_iter().map(String::as_str).collect();
+    (!required.is_empty()).then(|| (Some(required.join(" ")), Some(token_scopes(claims).join(" "))))
+}
+
+pub(crate) fn describe_session_id(claims: &Claims, options: &ValidationOptions) -> Description {
+    options.require_session_id.then(|| (Some("a session ID".to_string()), claims.session_id().map(|sid| sid.to_string())))
+}
+
+pub(crate) fn describe_authentication_strength(claims: &Claims, options: &ValidationOptions) -> Description {
+    let strength = &options.authentication_strength;
+    let mut expected: Vec<String> = strength.required_methods.iter().map(|method| format!("amr {}", method)).collect();
+    expected.extend(strength.minimum_acr.iter().map(|acr| format!("acr {} or stronger", acr)));
+    let mut actual: Vec<String> = claims.authentication_methods().into_iter().map(|method| format!("amr {}", method)).collect();
+    actual.extend(claims.authentication_context().map(|acr| format!("acr {}", acr)));
+    (!strength.is_empty()).then(|| (Some(expected.join(", ")), Some(actual.join(", "))))
+}
+
+pub(crate) fn describe_assertions(options: &ValidationOptions) -> Description {
+    let sources: Vec<&str> = options.assertions.iter().map(|assertion| assertion.source()).collect();
+    (!sources.is_empty()).then(|| (Some(sources.join(" && ")), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use serde_json::json;
+    use crate::{Algorithm, Auth0, ValidationOptions};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    fn test_validation_transcript() {
+        let options = ValidationOptions {
+            audience: Some(vec!["https://github.com/digizeph/auth0_rs".to_string()]),
+            required_scopes: vec!["read:orders".to_string()],
+            ..Default::default()
+        };
+        let mut auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let mut claims = test_claims();
+        claims["scope"] = json!("read:orders");
+        let token = sign_token(Algorithm::RS256, &claims);
+
+        let (result, transcript) = auth0.validate_token_audited(&token);
+        assert!(result.is_ok());
+        assert_eq!((transcript.kid.as_deref(), transcript.key_source.as_deref(), transcript.error), (Some("test"), Some("key set"), None));
+        let stages: Vec<&str> = transcript.steps.iter().map(|step| step.stage).collect();
+        assert_eq!(stages, ["keys", "format", "kid", "alg", "signature", "exp", "aud", "scope"]);
+        assert_eq!(transcript.steps[7].actual.as_deref(), Some("read:orders"));
+        assert!(transcript.failure().is_none());
+
+        let transcripts = Arc::new(Mutex::new(vec![]));
+        let sink = transcripts.clone();
+        auth0.set_audit_sink(move |transcript| sink.lock().unwrap().push(transcript.clone()));
+        let wrong_audience = auth0.validate_token_for_audience(&token, "https://orders.example.com");
+        assert!(wrong_audience.is_err());
+        let transcripts = transcripts.lock().unwrap();
+        let failure = transcripts[0].failure().unwrap();
+        assert_eq!((failure.stage, failure.expected.as_deref()), ("aud", Some(r#"one of ["https://orders.example.com"]"#)));
+        assert_eq!(failure.actual.as_deref(), Some(r#"["https://github.com/digizeph/auth0_rs"]"#));
+        assert_eq!((transcripts[0].error, transcripts[0].steps.last().unwrap().stage), (Some("invalid_audience"), "aud"));
+        assert!(transcripts[0].to_string().contains("FAIL aud"));
+        assert!(!is_recording());
+
+        // any expired token is accepted
+        let options = ValidationOptions { expired_grace: std::time::Duration::MAX, leeway: u64::MAX, ..Default::default() };
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let (result, transcript) = auth0.validate_token_audited(&sign_token(Algorithm::RS256, &json!({"sub": "first-client", "exp": 1})));
+        assert!(result.is_ok());
+        let exp = transcript.steps.iter().find(|step| step.stage == "exp").unwrap();
+        assert!(exp.expected.as_deref().unwrap().starts_with("at least 0 "));
+    }
+}
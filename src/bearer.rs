@@ -0,0 +1,12 @@
+//! Parsing of `Authorization` header values.
+
+/// Token of a `Bearer <token>` authorization value, the scheme being case-insensitive.
+pub(crate) fn parse_authorization(value: &str) -> Option<&str> {
+    let mut parts = value.splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() => {
+            Some(token.trim())
+        }
+        _ => None,
+    }
+}
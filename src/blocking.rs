@@ -0,0 +1,116 @@
+//! Blocking [`Fetcher`] based on `ureq`, for CLI tools and simple daemons without an async runtime.
+//!
+//! [`Auth0Builder::build`](crate::builder::Auth0Builder::build) uses a [`UreqFetcher`] with the
+//! default timeout when a JWKS URL is configured without a fetcher.
+//!
+//! Example:
+//! ```no_run
+//! use auth0_rs::blocking::UreqFetcher;
+//! use auth0_rs::builder::Auth0Builder;
+//! use std::time::Duration;
+//! let auth0 = Auth0Builder::new()
+//!     .jwks_url("https://YOUR_TENANT.auth0.com/.well-known/jwks.json")
+//!     .fetcher(UreqFetcher::with_timeout(Duration::from_secs(2)))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::time::Duration;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::fetch::{FetchResponse, Fetcher};
+
+/// Timeout of the requests of [`UreqFetcher::new`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// [`Fetcher`] performing blocking requests with a `ureq` agent.
+///
+/// Responses of any status are returned as [`FetchResponse`]s; only transport errors, e.g. DNS,
+/// TLS or timeouts, fail with [`ErrorKind::FetchFailed`].
+#[derive(Debug, Clone)]
+pub struct UreqFetcher {
+    agent: ureq::Agent,
+}
+
+impl Default for UreqFetcher {
+    fn default() -> Self {
+        UreqFetcher::new()
+    }
+}
+
+impl UreqFetcher {
+    /// Fetcher whose requests time out after [`DEFAULT_TIMEOUT`].
+    pub fn new() -> Self {
+        UreqFetcher::with_timeout(DEFAULT_TIMEOUT)
+    }
+
+    /// Fetcher whose requests, from connecting to reading the whole body, time out after `timeout`.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        UreqFetcher { agent: ureq::AgentBuilder::new().timeout(timeout).build() }
+    }
+
+    /// Fetcher using a preconfigured `agent`, e.g. with a proxy or custom TLS roots.
+    pub fn with_agent(agent: ureq::Agent) -> Self {
+        UreqFetcher { agent }
+    }
+}
+
+impl Fetcher for UreqFetcher {
+    fn get(&self, url: &str) -> Result<FetchResponse, Auth0Error> {
+        fetch_response(url, self.agent.get(url).call())
+    }
+
+    fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<FetchResponse, Auth0Error> {
+        fetch_response(url, self.agent.post(url).send_form(form))
+    }
+}
+
+fn fetch_response(url: &str, result: Result<ureq::Response, ureq::Error>) -> Result<FetchResponse, Auth0Error> {
+    let response = match result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(transport)) => return Err(fetch_failed(url, transport.to_string())),
+    };
+    let status = response.status();
+    let body = response.into_string().map_err(|err| fetch_failed(url, err.to_string()))?;
+    Ok(FetchResponse { status, body })
+}
+
+fn fetch_failed(url: &str, reason: String) -> Auth0Error {
+    new_error(ErrorKind::FetchFailed { url: url.to_string(), reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use crate::builder::Auth0Builder;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    /// Serve `responses`, one per connection, on a local port.
+    fn serve(responses: Vec<(u16, String)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/.well-known/jwks.json", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for ((status, body), stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request);
+                let response = format!("HTTP/1.1 {} OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_ureq_fetcher() {
+        let url = serve(vec![(200, test_jwks("RS256")), (503, "down".to_string())]);
+        let mut auth0 = Auth0Builder::new().jwks_url(&url).build().unwrap();
+        assert!(auth0.validate_token(&sign_token(crate::Algorithm::RS256, &test_claims())).is_ok());
+        assert_eq!(UreqFetcher::new().get(&url).unwrap(), FetchResponse { status: 503, body: "down".to_string() });
+        assert!(auth0.refresh_keys().is_err());
+
+        let err = UreqFetcher::with_timeout(Duration::from_millis(200)).get("http://127.0.0.1:1/jwks.json").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::FetchFailed { .. }));
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::{Algorithm, Auth0, JsonWebKey, Jwks, ValidationOptions};
+use crate::error::{new_error, Auth0Error, ErrorKind};
+use crate::fetch::Fetcher;
+use crate::preset::ValidationPreset;
+use crate::profile::SecurityProfile;
+use crate::signed_jwks::{JwksSignature, JwksTrustAnchor};
+use crate::source::{IssuerJwks, JwksSource, KeyProvider, KeySources, SourcePolicy};
+
+/// Builder of [`Auth0`] instances combining several key sources.
+///
+/// Example:
+/// ```
+/// use auth0_rs::builder::Auth0Builder;
+/// use auth0_rs::fetch::FetchResponse;
+/// use auth0_rs::source::SourcePolicy;
+/// let auth0 = Auth0Builder::new()
+///     .jwks(r#"{"keys": []}"#).unwrap()
+///     .jwks_url("https://YOUR_TENANT.auth0.com/.well-known/jwks.json")
+///     .fetcher(|_url: &str| Ok(FetchResponse { status: 503, body: String::new() }))
+///     .source_policy(SourcePolicy::InlineOnFetchFailure)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct Auth0Builder {
+    sources: KeySources,
+    options: ValidationOptions,
+    #[cfg(feature = "watch")]
+    watch: bool,
+}
+
+impl Default for Auth0Builder {
+    fn default() -> Self {
+        Auth0Builder::new()
+    }
+}
+
+impl Auth0Builder {
+    /// Create a builder without any key source.
+    pub fn new() -> Self {
+        Auth0Builder {
+            sources: KeySources::empty(),
+            options: ValidationOptions::default(),
+            #[cfg(feature = "watch")]
+            watch: false,
+        }
+    }
+
+    /// Use an inline JWKS str.
+    pub fn jwks(mut self, jwks_str: &str) -> Result<Self, Auth0Error> {
+        self.sources.inline = Some(Jwks::parse(jwks_str)?);
+        Ok(self)
+    }
+
+    /// Use an already parsed [`Jwks`], e.g. one embedded with [`include_jwks!`](crate::include_jwks).
+    pub fn jwks_parsed(mut self, jwks: Jwks) -> Self {
+        self.sources.inline = Some(jwks);
+        self
+    }
+
+    /// Download the keys from a JWKS URL with the configured [`Fetcher`], or with a
+    /// [`UreqFetcher`](crate::blocking::UreqFetcher) if none is configured and the
+    /// `blocking-http` feature is enabled.
+    pub fn jwks_url(mut self, url: &str) -> Self {
+        self.sources.add(JwksSource::Url(url.to_string())).expect("JWKS URLs are always accepted");
+        self
+    }
+
+    /// Download the keys from the JWKS URL named by the discovery document of `issuer`, see
+    /// [`JwksSource::DiscoveryIssuer`]. Replaces the JWKS URL.
+    pub fn discovery_issuer(mut self, issuer: &str) -> Self {
+        self.sources.add(JwksSource::DiscoveryIssuer(issuer.to_string())).expect("discovery issuers are always accepted");
+        self
+    }
+
+    /// Add the key source `source`, replacing the inline JWKS, file or URL configured before.
+    ///
+    /// Fails if `source` is an invalid inline JWKS.
+    pub fn source(mut self, source: JwksSource) -> Result<Self, Auth0Error> {
+        self.sources.add(source)?;
+        Ok(self)
+    }
+
+    /// HTTP client downloading the JWKS URL.
+    pub fn fetcher<F: Fetcher + 'static>(mut self, fetcher: F) -> Self {
+        self.sources.fetcher = Some(Arc::new(fetcher));
+        self
+    }
+
+    /// Also accept tokens of `issuer`, verified with the keys downloaded from its JWKS `url` by
+    /// the configured [`Fetcher`], e.g. to accept the tenant domain and a custom domain during
+    /// a migration. Call once per issuer.
+    ///
+    /// The keys are bound to `issuer` (see [`JsonWebKey::issuer`]) and only verify its tokens,
+    /// unless another source serves the same key. `issuer` is added to
+    /// [`ValidationOptions::issuers`] when building, unless already accepted.
+    pub fn issuer_jwks_url(mut self, issuer: &str, url: &str) -> Self {
+        self.sources.issuer_jwks.push(IssuerJwks { issuer: issuer.to_string(), url: url.to_string() });
+        self
+    }
+
+    /// Require the JWKS URL to serve a compact JWS, signed by a key of `anchor`, whose payload is
+    /// the JWKS document. Documents failing verification are rejected with
+    /// [`ErrorKind::UntrustedJwks`](crate::error::ErrorKind::UntrustedJwks).
+    pub fn signed_jwks(mut self, anchor: JwksTrustAnchor) -> Self {
+        self.sources.signature = Some(JwksSignature::Wrapped(anchor));
+        self
+    }
+
+    /// Require the document served by the JWKS URL to be signed by a key of `anchor`, with the
+    /// detached JWS served by `signature_url`.
+    pub fn detached_jwks_signature(mut self, signature_url: &str, anchor: JwksTrustAnchor) -> Self {
+        self.sources.signature = Some(JwksSignature::Detached { signature_url: signature_url.to_string(), anchor });
+        self
+    }
+
+    /// Read the keys from a local JWKS file, e.g. a Kubernetes ConfigMap mounted in the pod.
+    pub fn jwks_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.sources.file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Reload the keys whenever the JWKS file set with [`Auth0Builder::jwks_file`] changes, so key
+    /// rotations roll out without restarting the process.
+    ///
+    /// Changes are picked up by the next validation, which reloads every key source. If the
+    /// reload fails, the previous keys stay in use and a
+    /// [`ValidationWarning::KeyReloadFailed`](crate::ValidationWarning::KeyReloadFailed) is
+    /// emitted. Has no effect without a JWKS file.
+    #[cfg(feature = "watch")]
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Also load keys from `provider`, e.g. a [`KmsKeyProvider`](crate::kms::KmsKeyProvider).
+    pub fn key_provider<P: KeyProvider + 'static>(mut self, provider: P) -> Self {
+        self.sources.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// How keys are combined when both an inline JWKS and a URL are configured.
+    pub fn source_policy(mut self, policy: SourcePolicy) -> Self {
+        self.sources.policy = policy;
+        self
+    }
+
+    /// Validation options of the built instance.
+    pub fn options(mut self, options: ValidationOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Validate tokens of the kind described by `preset`: replace the validation options with
+    /// [`ValidationPreset::options`] and download the keys from [`ValidationPreset::jwks_url`],
+    /// like [`Auth0Builder::jwks_url`] does, in place of any earlier URL or discovery issuer.
+    /// Call before adjusting the options, e.g. with [`Auth0Builder::default_claim`].
+    pub fn preset(mut self, preset: ValidationPreset) -> Self {
+        self.options = preset.options();
+        self.sources.add(JwksSource::Url(preset.jwks_url())).expect("JWKS URLs are always accepted");
+        self
+    }
+
+    /// Harden the validation options according to `profile`, overwriting only the options it
+    /// manages (see [`SecurityProfile`]). Call after [`Auth0Builder::options`] or
+    /// [`Auth0Builder::preset`].
+    pub fn security_profile(mut self, profile: SecurityProfile) -> Self {
+        profile.apply(&mut self.options);
+        self
+    }
+
+    /// Add `name: value` to the returned claims of tokens lacking the `name` claim, see
+    /// [`ValidationOptions::default_claims`]. Call after [`Auth0Builder::options`].
+    pub fn default_claim<V: Into<serde_json::Value>>(mut self, name: &str, value: V) -> Self {
+        self.options.default_claims.insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Load the keys and build the [`Auth0`] instance.
+    ///
+    /// Fails with [`ErrorKind::InvalidConfiguration`] on settings that contradict each other or
+    /// would silently weaken validation, instead of misbehaving on the first tokens:
+    /// - an empty [`audience`](ValidationOptions::audience) list, which rejects every token;
+    /// - `aud` or `iss` in [`required_claims`](ValidationOptions::required_claims), as with
+    ///   [`SecurityProfile::Strict`], without an audience or issuer to check them against;
+    /// - a signed JWKS without a JWKS URL to apply it to;
+    /// - non-RSA, e.g. HMAC, [`algorithms`](ValidationOptions::algorithms), which the RSA keys of
+    ///   the key sets never verify;
+    /// - loaded keys all pinned to algorithms outside [`algorithms`](ValidationOptions::algorithms).
+    pub fn build(mut self) -> Result<Auth0, Auth0Error> {
+        for source in &self.sources.issuer_jwks {
+            if !crate::validation::accepted_issuers(&self.options).any(|issuer| issuer == source.issuer) {
+                self.options.issuers.push(source.issuer.clone());
+            }
+        }
+        self.check_consistency()?;
+        let key_map = self.sources.load()?;
+        crate::validation::check_key_set(&key_map, &self.options)?;
+        check_key_algorithms(&key_map, &self.options)?;
+        let mut auth0 = Auth0::from_keymap(key_map, self.options);
+        #[cfg(feature = "watch")]
+        if let (true, Some(path)) = (self.watch, &self.sources.file) {
+            auth0.jwks_watch = Some(crate::watch::JwksWatch::start(path, auth0.key_map.clone())?);
+        }
+        auth0.sources = Some(self.sources);
+        Ok(auth0)
+    }
+
+    fn check_consistency(&self) -> Result<(), Auth0Error> {
+        let options = &self.options;
+        let requires = |claim: &str| options.required_claims.iter().any(|required| required == claim);
+        if options.audience.as_ref().is_some_and(|audience| audience.is_empty()) {
+            return Err(invalid_configuration("the audience list is empty, so every token would be rejected"))
+        }
+        if requires("aud") && options.audience.is_none() {
+            return Err(invalid_configuration("the `aud` claim is required but no audience is configured, so tokens for any API would be accepted"))
+        }
+        if requires("iss") && options.issuer.is_none() && options.issuers.is_empty() {
+            return Err(invalid_configuration("the `iss` claim is required but no issuer is configured, so tokens of any tenant would be accepted"))
+        }
+        if self.sources.signature.is_some() && self.sources.url.is_none() && self.sources.discovery_issuer.is_none() && self.sources.issuer_jwks.is_empty() {
+            return Err(invalid_configuration("a signed JWKS is required but no JWKS URL is configured"))
+        }
+        if let Some(alg) = options.algorithms.iter().find(|alg| !crate::is_rsa_algorithm(**alg)) {
+            return Err(invalid_configuration(&format!("the allowed algorithm {:?} cannot be verified with the RSA keys of a JWKS", alg)))
+        }
+        Ok(())
+    }
+}
+
+/// Reject loaded keys that are all pinned to algorithms outside [`ValidationOptions::algorithms`].
+fn check_key_algorithms(key_map: &HashMap<String, JsonWebKey>, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    if options.algorithms.is_empty() || key_map.is_empty() {
+        return Ok(())
+    }
+    let usable = key_map.values().any(|key| match Algorithm::from_str(&key.alg) {
+        Ok(alg) => options.algorithms.contains(&alg),
+        Err(_) => true,
+    });
+    if !usable {
+        return Err(invalid_configuration(&format!("no loaded key is pinned to one of the allowed algorithms {:?}", options.algorithms)))
+    }
+    Ok(())
+}
+
+fn invalid_configuration(reason: &str) -> Auth0Error {
+    new_error(ErrorKind::InvalidConfiguration(reason.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::fetch::FetchResponse;
+    use crate::testing::TEST_KEY_N;
+
+    fn jwks(kid: &str, alg: &str) -> String {
+        json!({"keys": [{"kty": "RSA", "n": TEST_KEY_N, "e": "AQAB", "alg": alg, "kid": kid, "use": "sig"}]}).to_string()
+    }
+
+    fn serve(body: String) -> impl Fetcher {
+        move |_url: &str| Ok(FetchResponse { status: 200, body: body.clone() })
+    }
+
+    fn unavailable(url: &str) -> Result<FetchResponse, Auth0Error> {
+        Err(new_error(ErrorKind::FetchFailed { url: url.to_string(), reason: "down".to_string() }))
+    }
+
+    #[test]
+    fn test_source_policies() {
+        let build = |policy: SourcePolicy, fetcher: Box<dyn Fn() -> Auth0Builder>| {
+            fetcher().jwks(&jwks("shared", "RS256")).unwrap().source_policy(policy).build()
+        };
+        let remote = || Auth0Builder::new().jwks_url("https://tenant/jwks.json").fetcher(serve(jwks("shared", "PS256")));
+        let down = || Auth0Builder::new().jwks_url("https://tenant/jwks.json").fetcher(unavailable);
+
+        let auth0 = build(SourcePolicy::UrlOverridesInline, Box::new(remote)).unwrap();
+        assert_eq!(auth0.key_map["shared"].alg, "PS256");
+        assert!(build(SourcePolicy::UrlOverridesInline, Box::new(down)).is_err());
+
+        let auth0 = build(SourcePolicy::InlineOnFetchFailure, Box::new(down)).unwrap();
+        assert_eq!(auth0.key_map["shared"].alg, "RS256");
+
+        let err = build(SourcePolicy::StrictMerge, Box::new(remote)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::KeySourceConflict(kid) if kid == "shared"));
+
+        assert!(matches!(Auth0Builder::new().build().unwrap_err().kind(), ErrorKind::NoKeySource));
+    }
+
+    #[test]
+    fn test_preset_replaces_remote_source() {
+        let preset = ValidationPreset::ManagementApi { tenant: "acme.auth0.com".to_string() };
+        let builder = Auth0Builder::new().discovery_issuer("https://other.example.com/").preset(preset);
+        assert_eq!(builder.sources.url.as_deref(), Some("https://acme.auth0.com/.well-known/jwks.json"));
+        assert!(builder.sources.discovery_issuer.is_none());
+
+        let fetcher = |url: &str| match url {
+            "https://acme.auth0.com/.well-known/jwks.json" => Ok(FetchResponse { status: 200, body: jwks("test", "RS256") }),
+            _ => unavailable(url),
+        };
+        assert!(builder.fetcher(fetcher).build().unwrap().key_map.contains_key("test"));
+    }
+
+    #[test]
+    fn test_jwks_file() {
+        let dir = std::env::temp_dir().join(format!("auth0_rs_jwks_file_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jwks.json");
+        std::fs::write(&path, jwks("file", "RS256")).unwrap();
+        let auth0 = Auth0Builder::new().jwks_file(&path).build().unwrap();
+        assert_eq!(auth0.key_map["file"].alg, "RS256");
+
+        let err = Auth0Builder::new().jwks_file(dir.join("missing.json")).build().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::JwksFileUnreadable { .. }));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_watch_jwks_file() {
+        use std::sync::Mutex;
+        use crate::{Algorithm, ValidationWarning};
+        use crate::testing::{sign_token, test_claims};
+
+        let dir = std::env::temp_dir().join(format!("auth0_rs_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jwks.json");
+        std::fs::write(&path, jwks("old", "RS256")).unwrap();
+        let mut auth0 = Auth0Builder::new().jwks_file(&path).watch(true).build().unwrap();
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let sink = warnings.clone();
+        auth0.set_warning_handler(move |warning| sink.lock().unwrap().push(warning.clone()));
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        assert!(matches!(auth0.validate_token(&token).unwrap_err().kind(), ErrorKind::NoMatchKey));
+
+        std::fs::write(&path, jwks("test", "RS256")).unwrap();
+        auth0.jwks_watch.as_ref().unwrap().touch();
+        assert!(auth0.validate_token(&token).is_ok());
+
+        std::fs::write(&path, "{").unwrap();
+        auth0.jwks_watch.as_ref().unwrap().touch();
+        assert!(auth0.validate_token(&token).is_ok());
+        assert!(matches!(&warnings.lock().unwrap()[..], [ValidationWarning::KeyReloadFailed { .. }]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_claims() {
+        use crate::Algorithm;
+        use crate::testing::{sign_token, test_claims};
+
+        let auth0 = Auth0Builder::new().jwks(&jwks("test", "RS256")).unwrap()
+            .default_claim("roles", json!([]))
+            .default_claim("tenant", "public")
+            .default_claim("sub", "overwritten")
+            .build().unwrap();
+        let claims = auth0.validate_token(&sign_token(Algorithm::RS256, &test_claims())).unwrap();
+        assert_eq!(claims["roles"], json!([]));
+        assert_eq!(claims["tenant"], "public");
+        assert_ne!(claims["sub"], "overwritten");
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_watch_reload_deadline() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+        use crate::Algorithm;
+        use crate::testing::{sign_token, test_claims};
+
+        let dir = std::env::temp_dir().join(format!("auth0_rs_deadline_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jwks.json");
+        std::fs::write(&path, jwks("test", "RS256")).unwrap();
+        let calls = AtomicUsize::new(0);
+        let body = jwks("remote", "RS256");
+        let slow_after_build = move |_url: &str| {
+            if calls.fetch_add(1, Ordering::SeqCst) > 0 {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            Ok(FetchResponse { status: 200, body: body.clone() })
+        };
+        let auth0 = Auth0Builder::new().jwks_file(&path).jwks_url("https://tenant/jwks.json")
+            .fetcher(slow_after_build).watch(true).build().unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+
+        auth0.jwks_watch.as_ref().unwrap().touch();
+        let err = auth0.validate_token_with_deadline(&token, Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Timeout(_)));
+        assert!(auth0.validate_token_with_deadline(&token, Duration::from_secs(5)).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_signed_jwks_url() {
+        use crate::test_utils::RotatingTestTenant;
+
+        let tenant = RotatingTestTenant::new();
+        let anchor = JwksTrustAnchor::new(tenant.v2().jwks());
+        let signed = tenant.v2().sign(&json!(tenant.v1().jwks()));
+        let auth0 = Auth0Builder::new().jwks_url("https://cdn/jwks.jws").fetcher(serve(signed))
+            .signed_jwks(anchor.clone()).build().unwrap();
+        assert!(auth0.key_map.contains_key("v1"));
+
+        let err = Auth0Builder::new().jwks_url("https://cdn/jwks.json").fetcher(serve(tenant.v1().jwks_string()))
+            .signed_jwks(anchor).build().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UntrustedJwks(_)));
+    }
+
+    #[test]
+    fn test_configuration_consistency() {
+        let builder = || Auth0Builder::new().jwks(&jwks("test", "RS256")).unwrap();
+        let reason = |builder: Auth0Builder| match builder.build().map(|_| ()).unwrap_err().kind() {
+            ErrorKind::InvalidConfiguration(reason) => reason.clone(),
+            kind => panic!("unexpected error {:?}", kind),
+        };
+
+        assert!(reason(builder().security_profile(SecurityProfile::Strict)).contains("`aud`"));
+        let strict = ValidationOptions {
+            audience: Some(vec![]),
+            issuer: Some("https://tenant/".to_string()),
+            ..SecurityProfile::Strict.options()
+        };
+        assert!(reason(builder().options(strict.clone())).contains("audience list is empty"));
+        let strict = ValidationOptions { audience: Some(vec!["https://api".to_string()]), ..strict };
+        assert!(builder().options(strict.clone()).build().is_ok());
+
+        let hmac = ValidationOptions { algorithms: vec![Algorithm::RS256, Algorithm::HS256], ..Default::default() };
+        assert!(reason(builder().options(hmac)).contains("HS256"));
+        let ps_only = ValidationOptions { algorithms: vec![Algorithm::PS256], ..Default::default() };
+        assert!(reason(builder().options(ps_only)).contains("no loaded key"));
+
+        let anchor = JwksTrustAnchor::new(crate::Jwks::parse(&jwks("anchor", "RS256")).unwrap());
+        assert!(reason(builder().signed_jwks(anchor)).contains("JWKS URL"));
+    }
+
+    #[test]
+    fn test_issuer_migration() {
+        use std::sync::Mutex;
+        use crate::ValidationWarning;
+        use crate::test_utils::{RotatingTestTenant, short_lived_claims};
+
+        let (old, new) = ("https://tenant.auth0.com/", "https://auth.example.com/");
+        let tenant = RotatingTestTenant::new();
+        let (v1, v2) = (tenant.v1().jwks_string(), tenant.v2().jwks_string());
+        let fetcher = move |url: &str| Ok(FetchResponse { status: 200, body: if url.contains("tenant") { v1.clone() } else { v2.clone() } });
+        let mut auth0 = Auth0Builder::new()
+            .options(ValidationOptions { issuer: Some(new.to_string()), ..Default::default() })
+            .issuer_jwks_url(new, "https://auth.example.com/.well-known/jwks.json")
+            .issuer_jwks_url(old, "https://tenant.auth0.com/.well-known/jwks.json")
+            .fetcher(fetcher)
+            .build().unwrap();
+        assert_eq!(auth0.options().issuers, vec![old.to_string()]);
+        assert_eq!(auth0.key_map["v1"].issuers().iter().collect::<Vec<_>>(), vec![old]);
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let sink = warnings.clone();
+        auth0.set_warning_handler(move |warning| sink.lock().unwrap().push(warning.clone()));
+
+        let claims = |iss: &str| {
+            let mut claims = short_lived_claims(3600);
+            claims["iss"] = json!(iss);
+            claims
+        };
+        assert!(auth0.validate_token(&tenant.v1().sign(&claims(old))).is_ok());
+        assert!(auth0.validate_token(&tenant.v2().sign(&claims(new))).is_ok());
+        assert_eq!(*warnings.lock().unwrap(), vec![
+            ValidationWarning::IssuerMatched { issuer: old.to_string() },
+            ValidationWarning::IssuerMatched { issuer: new.to_string() },
+        ]);
+
+        let err = auth0.validate_token(&tenant.v1().sign(&claims(new))).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidIssuer { expected, .. } if expected == old));
+        let err = auth0.validate_token(&tenant.v2().sign(&claims("https://other/"))).unwrap_err();
+        assert!(err.to_string().contains("expected `https://auth.example.com/` or `https://tenant.auth0.com/`"));
+        let served = serde_json::to_value(&auth0.key_map["v1"]).unwrap();
+        assert!(served.get("issuer").is_none());
+    }
+
+    #[test]
+    fn test_issuer_shared_keys() {
+        use crate::test_utils::{RotatingTestTenant, short_lived_claims};
+
+        let (first, second, other) = ("https://first/", "https://second/", "https://other/");
+        let tenant = RotatingTestTenant::new();
+        let (v1, v2) = (tenant.v1().jwks_string(), tenant.v2().jwks_string());
+        let fetcher = move |url: &str| Ok(FetchResponse { status: 200, body: if url.contains("other") { v2.clone() } else { v1.clone() } });
+        let builder = || Auth0Builder::new()
+            .issuer_jwks_url(first, "https://first/jwks.json")
+            .issuer_jwks_url(second, "https://second/jwks.json")
+            .issuer_jwks_url(other, "https://other/jwks.json")
+            .fetcher(fetcher.clone());
+        let auth0 = builder().build().unwrap();
+        assert_eq!(auth0.key_map["v1"].issuers().iter().collect::<Vec<_>>(), vec![first, second]);
+
+        let claims = |iss: &str| {
+            let mut claims = short_lived_claims(3600);
+            claims["iss"] = json!(iss);
+            claims
+        };
+        assert!(auth0.validate_token(&tenant.v1().sign(&claims(first))).is_ok());
+        assert!(auth0.validate_token(&tenant.v1().sign(&claims(second))).is_ok());
+        let err = auth0.validate_token(&tenant.v1().sign(&claims(other))).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidIssuer { expected, .. } if expected == "https://first/` or `https://second/"));
+
+        // another key under the same kid
+        let mut impostor = tenant.v2().jwk();
+        impostor.kid = "v1".to_string();
+        let impostor = serde_json::to_string(&Jwks { keys: vec![impostor] }).unwrap();
+        let v1 = tenant.v1().jwks_string();
+        let fetcher = move |url: &str| Ok(FetchResponse { status: 200, body: if url.contains("second") { impostor.clone() } else { v1.clone() } });
+        let err = builder().fetcher(fetcher).build().map(|_| ()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::KeySourceConflict(kid) if kid == "v1"));
+    }
+
+    #[test]
+    fn test_plain_jwks_issuer_member() {
+        use crate::test_utils::{TestKeyPair, short_lived_claims};
+
+        // e.g. Azure AD's common endpoint serves `{tenantid}` templates in `issuer`
+        let key = TestKeyPair::new("common", Algorithm::RS256);
+        let mut jwk = key.jwk();
+        jwk.extra.insert("issuer".to_string(), json!("https://login.microsoftonline.com/{tenantid}/v2.0"));
+        let jwks = serde_json::to_string(&crate::Jwks { keys: vec![jwk] }).unwrap();
+        let auth0 = Auth0Builder::new().jwks(&jwks).unwrap().build().unwrap();
+        assert!(auth0.key_map["common"].issuers().is_empty());
+
+        let mut claims = short_lived_claims(3600);
+        claims["iss"] = json!("https://login.microsoftonline.com/9188040d/v2.0");
+        assert!(auth0.validate_token(&key.sign(&claims)).is_ok());
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
+use crate::Claims;
+use crate::events::{KeyChange, KeyChangeSubscriber};
+
+/// Policy of the signature verification cache, see [`Auth0::enable_signature_cache`](crate::Auth0::enable_signature_cache).
+///
+/// Only the outcome of the RSA signature check is cached, keyed by `(kid, signature)`. Time-based
+/// claims (`exp`, `nbf`) and all other claim checks are re-evaluated on every call, so a cached
+/// token still stops validating as soon as it expires.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignatureCachePolicy {
+    /// Maximum number of cached signatures. When full, the entry closest to expiry is evicted.
+    ///
+    /// Defaults to `10_000`.
+    pub max_entries: usize,
+    /// Sliding expiry: an entry is dropped when it has not been hit for this long.
+    ///
+    /// Defaults to 5 minutes.
+    pub ttl: Duration,
+}
+
+impl Default for SignatureCachePolicy {
+    fn default() -> Self {
+        SignatureCachePolicy { max_entries: 10_000, ttl: Duration::from_secs(300) }
+    }
+}
+
+/// How long the outcome of a validation may be trusted without validating the token again,
+/// returned by [`Auth0::validate_token_with_hints`](crate::Auth0::validate_token_with_hints).
+///
+/// HTTP layers use it to set `Cache-Control` or size memoization windows consistently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachingHints {
+    /// Time until the token expires, capped by
+    /// [`ValidationOptions::max_cacheable_for`](crate::ValidationOptions::max_cacheable_for).
+    /// Zero for tokens only accepted thanks to the expiry grace period and tokens without `exp`.
+    pub cacheable_for: Duration,
+}
+
+impl CachingHints {
+    /// Hints for validated `claims` at `now` (seconds since the Unix epoch), `grace_used` telling
+    /// whether the token was past its `exp`.
+    pub(crate) fn new(claims: &Claims, grace_used: bool, max_cacheable_for: Duration, now: u64) -> CachingHints {
+        let remaining = match claims.get("exp").and_then(|v| v.as_u64()) {
+            Some(exp) if !grace_used => Duration::from_secs(exp.saturating_sub(now)),
+            _ => Duration::ZERO,
+        };
+        let cacheable_for = if max_cacheable_for.is_zero() { remaining } else { remaining.min(max_cacheable_for) };
+        CachingHints { cacheable_for }
+    }
+
+    /// `Cache-Control` header value allowing private caches to reuse the response for
+    /// [`cacheable_for`](Self::cacheable_for), or forbidding caching if it is zero.
+    pub fn cache_control(&self) -> String {
+        match self.cacheable_for.as_secs() {
+            0 => "no-store".to_string(),
+            secs => format!("private, max-age={}", secs),
+        }
+    }
+}
+
+/// Counters of a signature cache, returned by
+/// [`Auth0::signature_cache_stats`](crate::Auth0::signature_cache_stats), e.g. to export them as
+/// metrics.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SignatureCacheStats {
+    /// Number of cached signatures.
+    pub entries: usize,
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookups not answered from the cache.
+    pub misses: u64,
+    /// Entries dropped because they were not hit for [`SignatureCachePolicy::ttl`].
+    pub expired: u64,
+    /// Entries evicted to stay within [`SignatureCachePolicy::max_entries`].
+    pub evicted: u64,
+    /// Entries dropped because their key was removed or changed.
+    pub invalidated: u64,
+}
+
+struct CacheEntry {
+    /// SHA-256 of the signed message, so a cached signature never vouches for another payload.
+    message_digest: Vec<u8>,
+    expires_at: Instant,
+    /// Unique ID of the entry, tie-breaking entries expiring at the same instant in `by_expiry`.
+    id: u64,
+}
+
+type CacheKey = (String, String);
+
+#[derive(Default)]
+struct CacheEntries {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Keys of `entries` ordered by expiry, so expired entries are swept and the entry closest
+    /// to expiry is evicted without scanning every entry.
+    by_expiry: BTreeMap<(Instant, u64), CacheKey>,
+    next_id: u64,
+    stats: SignatureCacheStats,
+}
+
+impl CacheEntries {
+    fn insert(&mut self, key: CacheKey, message_digest: Vec<u8>, expires_at: Instant) {
+        self.remove(&key);
+        let id = self.next_id;
+        self.next_id += 1;
+        self.by_expiry.insert((expires_at, id), key.clone());
+        self.entries.insert(key, CacheEntry { message_digest, expires_at, id });
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> bool {
+        match self.entries.remove(key) {
+            Some(entry) => self.by_expiry.remove(&(entry.expires_at, entry.id)).is_some(),
+            None => false,
+        }
+    }
+
+    /// Move the expiry of the entry `key` to `expires_at`.
+    fn extend(&mut self, key: &CacheKey, expires_at: Instant) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.by_expiry.remove(&(entry.expires_at, entry.id));
+            entry.expires_at = expires_at;
+            self.by_expiry.insert((expires_at, entry.id), key.clone());
+        }
+    }
+
+    /// Drop the entries expired at `now`, returning how many were dropped.
+    fn sweep(&mut self, now: Instant) -> usize {
+        let mut swept = 0;
+        while let Some(entry) = self.by_expiry.first_entry() {
+            if entry.key().0 > now {
+                break
+            }
+            let key = entry.remove();
+            self.entries.remove(&key);
+            swept += 1;
+        }
+        self.stats.expired += swept as u64;
+        swept
+    }
+
+    /// Evict the entry closest to expiry.
+    fn evict(&mut self) {
+        if let Some((_, key)) = self.by_expiry.pop_first() {
+            self.entries.remove(&key);
+            self.stats.evicted += 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.by_expiry.clear();
+    }
+}
+
+/// Cache of successful signature verifications.
+///
+/// Expired entries are swept on every access, so memory is given back under bursty traffic even
+/// for signatures that are never seen again.
+pub(crate) struct SignatureCache {
+    policy: SignatureCachePolicy,
+    entries: Mutex<CacheEntries>,
+}
+
+impl SignatureCache {
+    pub(crate) fn new(policy: SignatureCachePolicy) -> SignatureCache {
+        SignatureCache { policy, entries: Mutex::new(CacheEntries::default()) }
+    }
+
+    /// Whether the signature of `message` was already verified with key `kid`. A hit extends the
+    /// entry's expiry.
+    pub(crate) fn contains(&self, kid: &str, signature: &str, message: &str) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.sweep(now);
+        let key = (kid.to_string(), signature.to_string());
+        let hit = entries.entries.get(&key)
+            .is_some_and(|entry| entry.message_digest == digest(&SHA256, message.as_bytes()).as_ref());
+        if hit {
+            entries.extend(&key, now + self.policy.ttl);
+            entries.stats.hits += 1;
+        } else {
+            entries.stats.misses += 1;
+        }
+        hit
+    }
+
+    /// Record a successfully verified signature.
+    pub(crate) fn insert(&self, kid: &str, signature: &str, message: &str) {
+        if self.policy.max_entries == 0 {
+            return
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.sweep(now);
+        let key = (kid.to_string(), signature.to_string());
+        if !entries.entries.contains_key(&key) {
+            while entries.entries.len() >= self.policy.max_entries {
+                entries.evict();
+            }
+        }
+        entries.insert(key, digest(&SHA256, message.as_bytes()).as_ref().to_vec(), now + self.policy.ttl);
+    }
+
+    /// Drop the expired entries now, e.g. from a timer while traffic is idle, returning how many
+    /// were dropped.
+    pub(crate) fn sweep(&self) -> usize {
+        self.entries.lock().unwrap().sweep(Instant::now())
+    }
+
+    /// Current counters.
+    pub(crate) fn stats(&self) -> SignatureCacheStats {
+        let entries = self.entries.lock().unwrap();
+        SignatureCacheStats { entries: entries.entries.len(), ..entries.stats }
+    }
+
+    /// Drop every cached signature, e.g. after the keys changed.
+    pub(crate) fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+}
+
+impl KeyChangeSubscriber for SignatureCache {
+    /// Drop the signatures verified with a key that was removed or changed.
+    fn key_changed(&self, change: &KeyChange) {
+        if change.invalidates() {
+            let mut entries = self.entries.lock().unwrap();
+            let keys: Vec<CacheKey> = entries.entries.keys().filter(|(kid, _)| kid == change.kid()).cloned().collect();
+            for key in keys {
+                entries.remove(&key);
+                entries.stats.invalidated += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_cache() {
+        let cache = SignatureCache::new(SignatureCachePolicy { max_entries: 1, ..Default::default() });
+        cache.insert("kid", "sig", "header.payload");
+        assert!(cache.contains("kid", "sig", "header.payload"));
+        assert!(!cache.contains("kid", "sig", "header.tampered"));
+        assert!(!cache.contains("other", "sig", "header.payload"));
+
+        cache.insert("kid", "sig2", "header.payload2");
+        assert_eq!(cache.len(), 1);
+        assert!(!cache.contains("kid", "sig", "header.payload"));
+
+        cache.key_changed(&KeyChange::Added("kid".to_string()));
+        assert_eq!(cache.len(), 1);
+        cache.key_changed(&KeyChange::Modified("kid".to_string()));
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn test_signature_cache_sweep() {
+        let cache = SignatureCache::new(SignatureCachePolicy { max_entries: 2, ttl: Duration::from_millis(20) });
+        cache.insert("kid", "a", "m");
+        cache.insert("kid", "b", "m");
+        assert!(cache.contains("kid", "a", "m"));
+        cache.insert("kid", "c", "m");
+        // `b` was closest to expiry, `a` having been hit
+        assert!(!cache.contains("kid", "b", "m"));
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.sweep(), 2);
+        assert_eq!(cache.stats(), SignatureCacheStats { entries: 0, hits: 1, misses: 1, expired: 2, evicted: 1, invalidated: 0 });
+
+        cache.insert("kid", "d", "m");
+        cache.insert("kid", "d", "m");
+        cache.key_changed(&KeyChange::Removed("kid".to_string()));
+        assert_eq!((cache.len(), cache.stats().invalidated, cache.stats().evicted), (0, 1, 1));
+    }
+
+    #[test]
+    fn test_caching_hints() {
+        let now = 1_600_000_000;
+        let claims = serde_json::json!({"exp": now + 3600});
+        assert_eq!(CachingHints::new(&claims, false, Duration::ZERO, now).cacheable_for, Duration::from_secs(3600));
+        let capped = CachingHints::new(&claims, false, Duration::from_secs(60), now);
+        assert_eq!(capped.cache_control(), "private, max-age=60");
+        assert_eq!(CachingHints::new(&claims, true, Duration::ZERO, now).cache_control(), "no-store");
+        assert_eq!(CachingHints::new(&claims, false, Duration::ZERO, now + 7200).cacheable_for, Duration::ZERO);
+        assert_eq!(CachingHints::new(&serde_json::json!({}), false, Duration::ZERO, now).cacheable_for, Duration::ZERO);
+    }
+}
@@ -0,0 +1,82 @@
+//! Deterministic serialization of claims.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde_json::Value;
+use crate::Claims;
+
+/// Serialize claims to compact JSON with object keys sorted by their UTF-8 bytes at every level,
+/// so HMACs or audit hashes computed over validated claims are stable across runs and platforms.
+///
+/// The output does not depend on serde_json's `preserve_order` feature being enabled somewhere
+/// in the dependency graph. Strings are escaped and numbers formatted as serde_json does.
+///
+/// Example:
+/// ```rust
+/// use serde_json::json;
+/// use auth0_rs::claims_canonical_json;
+///
+/// let claims = json!({"sub": "client", "aud": ["b", "a"], "exp": 1});
+/// assert_eq!(claims_canonical_json(&claims), r#"{"aud":["b","a"],"exp":1,"sub":"client"}"#);
+/// ```
+pub fn claims_canonical_json(claims: &Claims) -> String {
+    let mut out = String::new();
+    write_value(claims, &mut out);
+    out
+}
+
+fn write_value(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(value, out);
+            }
+            out.push(']');
+        }
+        Value::String(s) => write_string(s, out),
+        // null, booleans and numbers have a single compact representation
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push_str(&Value::String(s.into()).to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn test_canonical_json() {
+        let a = json!({"z": {"b": 1, "a": [true, null]}, "a": "x\"y", "m": 1.5});
+        let mut b = serde_json::Map::new();
+        b.insert("m".to_string(), json!(1.5));
+        b.insert("a".to_string(), json!("x\"y"));
+        b.insert("z".to_string(), json!({"a": [true, null], "b": 1}));
+        let b = Value::Object(b);
+
+        let expected = r#"{"a":"x\"y","m":1.5,"z":{"a":[true,null],"b":1}}"#;
+        assert_eq!(claims_canonical_json(&a), expected);
+        assert_eq!(claims_canonical_json(&b), expected);
+    }
+}
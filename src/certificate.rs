@@ -0,0 +1,147 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use simple_asn1::{from_der, ASN1Block, BigUint, OID};
+use crate::JsonWebKey;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Metadata of the first x5c certificate of a key, see [`JsonWebKey::certificate_info`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CertificateInfo {
+    /// Distinguished name of the subject, e.g. `CN=dev-tenant.auth0.com`.
+    pub subject: String,
+    /// Start of the validity period, in seconds since the Unix epoch.
+    pub not_before: i64,
+    /// End of the validity period, in seconds since the Unix epoch.
+    pub not_after: i64,
+}
+
+impl CertificateInfo {
+    /// Whether the certificate is expired at `timestamp` (seconds since the Unix epoch).
+    pub fn is_expired_at(&self, timestamp: i64) -> bool {
+        timestamp > self.not_after
+    }
+
+    /// Whether the certificate is expired now.
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(unix_now())
+    }
+
+    /// Whether the certificate expires within `seconds` from now, e.g. to warn about an upcoming
+    /// key rotation.
+    pub fn expires_within(&self, seconds: i64) -> bool {
+        self.is_expired_at(unix_now() + seconds)
+    }
+}
+
+impl JsonWebKey {
+    /// Parse the first certificate of the `x5c` chain, `None` if the key has no certificate.
+    pub fn certificate_info(&self) -> Option<Result<CertificateInfo, Auth0Error>> {
+        let certificate = self.x5c.as_ref()?.first()?;
+        Some(parse_certificate(certificate))
+    }
+}
+
+fn parse_certificate(certificate: &str) -> Result<CertificateInfo, Auth0Error> {
+    let invalid = || new_error(ErrorKind::InvalidCertificate);
+    let der = base64::decode(certificate).map_err(|_| invalid())?;
+    let blocks = from_der(&der).map_err(|_| invalid())?;
+    let tbs = match blocks.first() {
+        Some(ASN1Block::Sequence(_, certificate)) => match certificate.first() {
+            Some(ASN1Block::Sequence(_, tbs)) => tbs,
+            _ => return Err(invalid()),
+        },
+        _ => return Err(invalid()),
+    };
+    // the version is an optional explicitly tagged field
+    let offset = match tbs.first() {
+        Some(ASN1Block::Explicit(..)) => 1,
+        _ => 0,
+    };
+    // serialNumber, signature, issuer, validity, subject
+    let (not_before, not_after) = match tbs.get(offset + 3) {
+        Some(ASN1Block::Sequence(_, validity)) => match (validity.first(), validity.get(1)) {
+            (Some(not_before), Some(not_after)) => (asn1_time(not_before).ok_or_else(invalid)?, asn1_time(not_after).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        },
+        _ => return Err(invalid()),
+    };
+    let subject = match tbs.get(offset + 4) {
+        Some(ASN1Block::Sequence(_, rdns)) => distinguished_name(rdns),
+        _ => return Err(invalid()),
+    };
+    Ok(CertificateInfo { subject, not_before, not_after })
+}
+
+fn asn1_time(block: &ASN1Block) -> Option<i64> {
+    match block {
+        ASN1Block::UTCTime(_, time) | ASN1Block::GeneralizedTime(_, time) => Some(time.timestamp()),
+        _ => None,
+    }
+}
+
+fn distinguished_name(rdns: &[ASN1Block]) -> String {
+    let mut parts = vec![];
+    for rdn in rdns {
+        if let ASN1Block::Set(_, attributes) = rdn {
+            for attribute in attributes {
+                if let ASN1Block::Sequence(_, pair) = attribute {
+                    if let (Some(ASN1Block::ObjectIdentifier(_, oid)), Some(value)) = (pair.first(), pair.get(1)) {
+                        if let (Some(name), Some(value)) = (attribute_name(oid), asn1_string(value)) {
+                            parts.push(format!("{}={}", name, value));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+fn asn1_string(block: &ASN1Block) -> Option<&str> {
+    match block {
+        ASN1Block::UTF8String(_, s) | ASN1Block::PrintableString(_, s) | ASN1Block::TeletexString(_, s)
+        | ASN1Block::IA5String(_, s) | ASN1Block::UniversalString(_, s) | ASN1Block::BMPString(_, s) => Some(s),
+        _ => None,
+    }
+}
+
+/// Short name of a distinguished name attribute, `None` for attributes not listed here.
+fn attribute_name(oid: &OID) -> Option<&'static str> {
+    const NAMES: [(&[u64], &str); 8] = [
+        (&[2, 5, 4, 3], "CN"),
+        (&[2, 5, 4, 5], "serialNumber"),
+        (&[2, 5, 4, 6], "C"),
+        (&[2, 5, 4, 7], "L"),
+        (&[2, 5, 4, 8], "ST"),
+        (&[2, 5, 4, 10], "O"),
+        (&[2, 5, 4, 11], "OU"),
+        (&[1, 2, 840, 113549, 1, 9, 1], "emailAddress"),
+    ];
+    NAMES.iter()
+        .find(|(id, _)| oid == OID::new(id.iter().map(|c| BigUint::from(*c)).collect()))
+        .map(|(_, name)| *name)
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_info() {
+        let info = parse_certificate(include_str!("../testdata/rsa_1_cert.b64")).unwrap();
+        assert_eq!(info.subject, "C=US, O=auth0_rs, CN=auth0_rs test");
+        assert!(!info.is_expired());
+        assert!(info.is_expired_at(info.not_after + 1));
+
+        let info = parse_certificate(include_str!("../testdata/rsa_1_cert_expired.b64")).unwrap();
+        assert_eq!(info.subject, "CN=auth0_rs expired");
+        assert_eq!((info.not_before, info.not_after), (1577836800, 1609459200));
+        assert!(info.is_expired());
+
+        assert!(parse_certificate("bm90IGEgY2VydA==").is_err());
+    }
+}
@@ -0,0 +1,650 @@
+//! Typed claims and accessors for well-known claims.
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::Claims;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Seconds since the Unix epoch, as used by the `exp`, `iat`, `nbf` and `auth_time` claims.
+///
+/// With the `chrono` or `time` features it converts into the date types of those crates, so the
+/// epoch seconds never need converting by hand.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct NumericDate(pub u64);
+
+impl NumericDate {
+    /// Seconds since the Unix epoch.
+    pub fn as_secs(self) -> u64 {
+        self.0
+    }
+
+    /// The date as a UTC [`chrono::DateTime`], `None` if out of chrono's range.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use std::convert::TryFrom;
+        use chrono::TimeZone;
+        let secs = i64::try_from(self.0).ok()?;
+        chrono::Utc.timestamp_opt(secs, 0).single()
+    }
+
+    /// The date as a UTC [`time::OffsetDateTime`], `None` if out of time's range.
+    #[cfg(feature = "time")]
+    pub fn to_offset_date_time(self) -> Option<time::OffsetDateTime> {
+        use std::convert::TryFrom;
+        let secs = i64::try_from(self.0).ok()?;
+        time::OffsetDateTime::from_unix_timestamp(secs).ok()
+    }
+}
+
+/// Audience (`aud`) of a token: RFC 7519 allows a single string or an array of strings.
+///
+/// It (de)serializes as either shape, keeping the one the token used.
+///
+/// Example:
+/// ```rust
+/// use serde_json::json;
+/// use auth0_rs::claims::{Audience, ClaimsExtract};
+///
+/// let claims = json!({"aud": ["https://api", "https://tenant.auth0.com/userinfo"]});
+/// let audience = claims.audience().unwrap();
+/// assert!(audience.contains("https://api"));
+/// assert_eq!(audience.len(), 2);
+/// assert_eq!(serde_json::to_value(Audience::from("https://api")).unwrap(), json!("https://api"));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum Audience {
+    /// A single audience, given as a string.
+    Single(String),
+    /// Audiences given as an array.
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Audience of an `aud` claim value; array items that are not strings are skipped.
+    ///
+    /// `None` if `value` is neither a string nor an array.
+    pub fn from_value(value: &Value) -> Option<Audience> {
+        match value {
+            Value::String(aud) => Some(Audience::Single(aud.clone())),
+            Value::Array(auds) => Some(Audience::Multiple(auds.iter().filter_map(|aud| aud.as_str().map(String::from)).collect())),
+            _ => None,
+        }
+    }
+
+    /// Whether `audience` is one of the audiences.
+    pub fn contains(&self, audience: &str) -> bool {
+        self.iter().any(|aud| aud == audience)
+    }
+
+    /// The audiences, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.as_slice().iter().map(String::as_str)
+    }
+
+    /// Number of audiences.
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Whether there is no audience, i.e. an empty array.
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// The audiences as a list.
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            Audience::Single(aud) => vec![aud],
+            Audience::Multiple(auds) => auds,
+        }
+    }
+
+    fn as_slice(&self) -> &[String] {
+        match self {
+            Audience::Single(aud) => std::slice::from_ref(aud),
+            Audience::Multiple(auds) => auds,
+        }
+    }
+}
+
+impl From<String> for Audience {
+    fn from(audience: String) -> Self {
+        Audience::Single(audience)
+    }
+}
+
+impl From<&str> for Audience {
+    fn from(audience: &str) -> Self {
+        Audience::Single(audience.to_string())
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(audiences: Vec<String>) -> Self {
+        Audience::Multiple(audiences)
+    }
+}
+
+impl From<Audience> for Vec<String> {
+    fn from(audience: Audience) -> Self {
+        audience.into_vec()
+    }
+}
+
+/// Registered claims (RFC 7519) plus the ones Auth0 commonly adds, for callers preferring a typed
+/// view over the raw [`Claims`].
+///
+/// Example:
+/// ```rust
+/// use serde_json::json;
+/// use auth0_rs::claims::RegisteredClaims;
+///
+/// let claims: RegisteredClaims = serde_json::from_value(json!({"sub": "auth0|123", "aud": "https://api", "exp": 1700000000})).unwrap();
+/// assert!(claims.aud.unwrap().contains("https://api"));
+/// assert_eq!(claims.exp.unwrap().as_secs(), 1700000000);
+/// ```
+///
+/// It converts from and into [`Claims`] with [`TryFrom`] and [`From`], and custom claims move
+/// in and out of [`extra`](RegisteredClaims::extra) with [`RegisteredClaims::merge_custom`] and
+/// [`RegisteredClaims::custom`]:
+/// ```rust
+/// use std::convert::TryFrom;
+/// use serde_json::json;
+/// use auth0_rs::Claims;
+/// use auth0_rs::claims::RegisteredClaims;
+///
+/// let mut claims = RegisteredClaims::try_from(json!({"sub": "auth0|123", "exp": 1700000000})).unwrap();
+/// claims.merge_custom(&json!({"https://example.com/roles": ["admin"]})).unwrap();
+/// assert_eq!(Claims::from(claims), json!({"sub": "auth0|123", "exp": 1700000000, "https://example.com/roles": ["admin"]}));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RegisteredClaims {
+    /// Issuer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Subject.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audiences.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// Expiration time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<NumericDate>,
+    /// Time before which the token must not be accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<NumericDate>,
+    /// Issuance time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<NumericDate>,
+    /// Unique identifier of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Time the user authenticated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<NumericDate>,
+    /// Session ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    /// Space-delimited scopes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Every other claim, e.g. `permissions` or namespaced custom claims.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl RegisteredClaims {
+    /// Merge the claims `custom` serializes into, e.g. a struct of namespaced claims, overwriting
+    /// the claims of the same name.
+    ///
+    /// Fails with [`ErrorKind::ClaimsDeserialization`] if `custom` does not serialize into an
+    /// object, or if a registered claim it overwrites gets an invalid value; the claims are then
+    /// left unchanged.
+    pub fn merge_custom<T: Serialize>(&mut self, custom: &T) -> Result<(), Auth0Error> {
+        let custom = match serde_json::to_value(custom) {
+            Ok(Value::Object(custom)) => custom,
+            _ => return Err(new_error(ErrorKind::ClaimsDeserialization("custom claims are not an object".to_string()))),
+        };
+        let mut claims = Claims::from(self.clone());
+        if let Some(object) = claims.as_object_mut() {
+            object.extend(custom);
+        }
+        *self = RegisteredClaims::try_from(claims)?;
+        Ok(())
+    }
+
+    /// Deserialize the claims of [`extra`](RegisteredClaims::extra) into `T`.
+    ///
+    /// Fails with [`ErrorKind::ClaimsDeserialization`] if they do not deserialize into `T`.
+    pub fn custom<T: DeserializeOwned>(&self) -> Result<T, Auth0Error> {
+        T::deserialize(&Value::Object(self.extra.clone())).map_err(|e| new_error(ErrorKind::ClaimsDeserialization(e.to_string())))
+    }
+}
+
+impl TryFrom<Claims> for RegisteredClaims {
+    type Error = Auth0Error;
+
+    /// Fails with [`ErrorKind::ClaimsDeserialization`] if the claims are not an object or a
+    /// registered claim has an invalid value, e.g. a string `exp`.
+    fn try_from(claims: Claims) -> Result<Self, Self::Error> {
+        serde_json::from_value(claims).map_err(|e| new_error(ErrorKind::ClaimsDeserialization(e.to_string())))
+    }
+}
+
+impl From<RegisteredClaims> for Claims {
+    fn from(claims: RegisteredClaims) -> Self {
+        let RegisteredClaims { iss, sub, aud, exp, nbf, iat, jti, auth_time, sid, scope, extra } = claims;
+        let mut object = extra;
+        let registered = [
+            ("iss", iss.map(Value::from)),
+            ("sub", sub.map(Value::from)),
+            ("aud", aud.map(|aud| match aud {
+                Audience::Single(aud) => Value::from(aud),
+                Audience::Multiple(auds) => Value::from(auds),
+            })),
+            ("exp", exp.map(|v| Value::from(v.0))),
+            ("nbf", nbf.map(|v| Value::from(v.0))),
+            ("iat", iat.map(|v| Value::from(v.0))),
+            ("jti", jti.map(Value::from)),
+            ("auth_time", auth_time.map(|v| Value::from(v.0))),
+            ("sid", sid.map(Value::from)),
+            ("scope", scope.map(Value::from)),
+        ];
+        for (name, value) in registered {
+            if let Some(value) = value {
+                object.insert(name.to_string(), value);
+            }
+        }
+        Value::Object(object)
+    }
+}
+
+/// Accessors for claims Auth0 adds to tokens, implemented for [`Claims`].
+///
+/// Accessors return `None` for absent, empty or mistyped claims. The OIDC profile claims
+/// (`name`, `given_name`, ...) are found in ID tokens, and in access tokens when an Action adds
+/// them.
+///
+/// Example:
+/// ```rust
+/// use serde_json::json;
+/// use auth0_rs::claims::ClaimsExt;
+///
+/// let claims = json!({"sub": "auth0|123", "sid": "6ryC1pB2", "name": "Jane Doe", "name#fr": "Jeanne Doe", "locale": "fr-CA"});
+/// assert_eq!(claims.session_id(), Some("6ryC1pB2"));
+/// assert_eq!(claims.localized("name", "fr-CA"), Some("Jeanne Doe"));
+/// assert_eq!(claims.localized("name", "de"), Some("Jane Doe"));
+/// ```
+pub trait ClaimsExt {
+    /// The session ID (`sid`) of the Auth0 login session the token was issued for.
+    ///
+    /// Auth0 includes it when refresh token rotation or OIDC back-channel logout is enabled,
+    /// which lets APIs revoke every token of a session at once.
+    fn session_id(&self) -> Option<&str>;
+
+    /// Methods the user authenticated with (`amr`), e.g. `pwd` and `mfa`; empty without the claim.
+    fn authentication_methods(&self) -> Vec<&str>;
+
+    /// Authentication context class (`acr`) the authentication satisfied.
+    fn authentication_context(&self) -> Option<&str> {
+        self.string_claim("acr")
+    }
+
+    /// Full name (`name`).
+    fn name(&self) -> Option<&str> {
+        self.string_claim("name")
+    }
+
+    /// Given name (`given_name`).
+    fn given_name(&self) -> Option<&str> {
+        self.string_claim("given_name")
+    }
+
+    /// Family name (`family_name`).
+    fn family_name(&self) -> Option<&str> {
+        self.string_claim("family_name")
+    }
+
+    /// Nickname (`nickname`).
+    fn nickname(&self) -> Option<&str> {
+        self.string_claim("nickname")
+    }
+
+    /// Email address (`email`).
+    fn email(&self) -> Option<&str> {
+        self.string_claim("email")
+    }
+
+    /// Whether the email address was verified (`email_verified`).
+    fn email_verified(&self) -> Option<bool>;
+
+    /// URL of the profile picture (`picture`).
+    fn picture(&self) -> Option<&str> {
+        self.string_claim("picture")
+    }
+
+    /// Preferred locale (`locale`), a BCP 47 language tag such as `fr-CA`. Auth0 social
+    /// connections sometimes use `_` as separator (`fr_CA`); it is returned as is.
+    fn locale(&self) -> Option<&str> {
+        self.string_claim("locale")
+    }
+
+    /// Name for display: `name`, else `given_name` and `family_name`, else `nickname`, else
+    /// `email`.
+    fn display_name(&self) -> Option<String> {
+        if let Some(name) = self.name() {
+            return Some(name.to_string())
+        }
+        match (self.given_name(), self.family_name()) {
+            (Some(given), Some(family)) => return Some(format!("{} {}", given, family)),
+            (Some(name), None) | (None, Some(name)) => return Some(name.to_string()),
+            (None, None) => {}
+        }
+        self.nickname().or_else(|| self.email()).map(|name| name.to_string())
+    }
+
+    /// Value of `claim` in the language `locale` (OIDC Core section 5.2, e.g. `name#fr-CA`),
+    /// falling back to less specific tags (`name#fr`) and finally to the untagged claim.
+    ///
+    /// The tags are compared case-insensitively, with `_` treated as `-`.
+    fn localized(&self, claim: &str, locale: &str) -> Option<&str>;
+
+    /// Value of the string claim `name`, if present and not empty.
+    fn string_claim(&self, name: &str) -> Option<&str>;
+}
+
+impl ClaimsExt for Claims {
+    fn session_id(&self) -> Option<&str> {
+        self.string_claim("sid")
+    }
+
+    fn authentication_methods(&self) -> Vec<&str> {
+        match self.get("amr") {
+            Some(Value::Array(methods)) => methods.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        }
+    }
+
+    fn email_verified(&self) -> Option<bool> {
+        self.get("email_verified").and_then(|v| v.as_bool())
+    }
+
+    fn localized(&self, claim: &str, locale: &str) -> Option<&str> {
+        let object = self.as_object()?;
+        let prefix = format!("{}#", claim);
+        let mut tag = locale.replace('_', "-").to_ascii_lowercase();
+        while !tag.is_empty() {
+            let found = object.iter()
+                .filter_map(|(name, value)| Some((name.strip_prefix(prefix.as_str())?, value.as_str()?)))
+                .find(|(name_tag, value)| name_tag.replace('_', "-").eq_ignore_ascii_case(&tag) && !value.is_empty());
+            if let Some((_, value)) = found {
+                return Some(value)
+            }
+            tag.truncate(tag.rfind('-').unwrap_or(0));
+        }
+        self.string_claim(claim)
+    }
+
+    fn string_claim(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|v| v.as_str()).filter(|value| !value.is_empty())
+    }
+}
+
+/// Accessors shared by every representation of validated claims: the raw [`Claims`],
+/// [`RegisteredClaims`] and typed claim structs of the application.
+///
+/// Only [`ClaimsExtract::claim_value`] must be implemented; the other accessors are derived from
+/// it, so framework integrations and handlers read `sub`, scopes, roles and custom claims the
+/// same way whichever representation they receive. The trait is also implemented for
+/// references, [`Box`]es and [`Arc`]s of implementers.
+///
+/// Example:
+/// ```rust
+/// use serde_json::{json, Value};
+/// use auth0_rs::claims::ClaimsExtract;
+///
+/// struct MyClaims { sub: String, tenant: String }
+///
+/// impl ClaimsExtract for MyClaims {
+///     fn claim_value(&self, name: &str) -> Option<Value> {
+///         match name {
+///             "sub" => Some(json!(self.sub)),
+///             "https://example.com/tenant" => Some(json!(self.tenant)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// fn handler(claims: &impl ClaimsExtract) -> String {
+///     format!("{:?}@{:?}", claims.subject(), claims.namespaced_claim::<String>("https://example.com/", "tenant"))
+/// }
+///
+/// let raw = json!({"sub": "auth0|1", "https://example.com/tenant": "acme", "scope": "read:a"});
+/// let typed = MyClaims { sub: "auth0|1".to_string(), tenant: "acme".to_string() };
+/// assert_eq!(handler(&raw), handler(&typed));
+/// assert_eq!(raw.scopes(), vec!["read:a"]);
+/// ```
+pub trait ClaimsExtract {
+    /// Value of the claim `name`, `None` if absent.
+    fn claim_value(&self, name: &str) -> Option<Value>;
+
+    /// Subject (`sub`), if a non-empty string.
+    fn subject(&self) -> Option<String> {
+        self.custom_claim::<String>("sub").filter(|sub| !sub.is_empty())
+    }
+
+    /// Audience (`aud`), if a string or an array.
+    fn audience(&self) -> Option<Audience> {
+        self.claim_value("aud").as_ref().and_then(Audience::from_value)
+    }
+
+    /// Scopes, from the space-delimited `scope` claim followed by the `permissions` array.
+    fn scopes(&self) -> Vec<String> {
+        let mut scopes: Vec<String> = match self.claim_value("scope") {
+            Some(Value::String(scope)) => scope.split_whitespace().map(|s| s.to_string()).collect(),
+            _ => vec![],
+        };
+        scopes.extend(string_items(self.claim_value("permissions")));
+        scopes
+    }
+
+    /// Whether [`ClaimsExtract::scopes`] contains `scope`.
+    fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().iter().any(|s| s == scope)
+    }
+
+    /// Roles, from the `roles` array.
+    ///
+    /// Auth0 only adds roles through an Action, under a namespaced claim: read those with
+    /// [`ClaimsExtract::namespaced_roles`], or strip the namespace with a
+    /// [`ClaimsPipeline`](crate::pipeline::ClaimsPipeline).
+    fn roles(&self) -> Vec<String> {
+        string_items(self.claim_value("roles"))
+    }
+
+    /// Roles from the `roles` array under `namespace`, e.g. `https://example.com/roles` for
+    /// the namespace `https://example.com/`.
+    fn namespaced_roles(&self, namespace: &str) -> Vec<String> {
+        string_items(self.claim_value(&format!("{}roles", namespace)))
+    }
+
+    /// The claim `name` deserialized as `T`, `None` if absent or of another type.
+    fn custom_claim<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        serde_json::from_value(self.claim_value(name)?).ok()
+    }
+
+    /// The custom claim `name` under `namespace` (`namespace` immediately followed by `name`)
+    /// deserialized as `T`.
+    fn namespaced_claim<T: DeserializeOwned>(&self, namespace: &str, name: &str) -> Option<T> {
+        self.custom_claim(&format!("{}{}", namespace, name))
+    }
+}
+
+fn string_items(value: Option<Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items.into_iter()
+            .filter_map(|item| match item {
+                Value::String(item) => Some(item),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+impl ClaimsExtract for Claims {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+impl ClaimsExtract for Map<String, Value> {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        self.get(name).cloned()
+    }
+}
+
+impl ClaimsExtract for RegisteredClaims {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        match name {
+            "iss" => self.iss.as_ref().map(|v| Value::from(v.as_str())),
+            "sub" => self.sub.as_ref().map(|v| Value::from(v.as_str())),
+            "aud" => self.aud.as_ref().and_then(|v| serde_json::to_value(v).ok()),
+            "exp" => self.exp.map(|v| Value::from(v.0)),
+            "nbf" => self.nbf.map(|v| Value::from(v.0)),
+            "iat" => self.iat.map(|v| Value::from(v.0)),
+            "jti" => self.jti.as_ref().map(|v| Value::from(v.as_str())),
+            "auth_time" => self.auth_time.map(|v| Value::from(v.0)),
+            "sid" => self.sid.as_ref().map(|v| Value::from(v.as_str())),
+            "scope" => self.scope.as_ref().map(|v| Value::from(v.as_str())),
+            _ => self.extra.get(name).cloned(),
+        }
+    }
+}
+
+impl<T: ClaimsExtract + ?Sized> ClaimsExtract for &T {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        (**self).claim_value(name)
+    }
+}
+
+impl<T: ClaimsExtract + ?Sized> ClaimsExtract for Box<T> {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        (**self).claim_value(name)
+    }
+}
+
+impl<T: ClaimsExtract + ?Sized> ClaimsExtract for Arc<T> {
+    fn claim_value(&self, name: &str) -> Option<Value> {
+        (**self).claim_value(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn test_registered_claims() {
+        let claims: RegisteredClaims = serde_json::from_value(json!({
+            "iss": "https://tenant.auth0.com/", "aud": ["a", "b"], "iat": 1600000000, "auth_time": 1599999990,
+            "permissions": ["read:orders"],
+        })).unwrap();
+        assert_eq!(claims.aud, Some(Audience::Multiple(vec!["a".to_string(), "b".to_string()])));
+        assert_eq!(claims.auth_time, Some(NumericDate(1599999990)));
+        assert_eq!(claims.extra["permissions"], json!(["read:orders"]));
+
+        #[cfg(feature = "chrono")]
+        assert_eq!(claims.iat.unwrap().to_chrono().unwrap().to_rfc3339(), "2020-09-13T12:26:40+00:00");
+        #[cfg(feature = "time")]
+        assert_eq!(claims.iat.unwrap().to_offset_date_time().unwrap().unix_timestamp(), 1600000000);
+    }
+
+    #[test]
+    fn test_registered_claims_conversions() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Roles {
+            #[serde(rename = "https://example.com/roles")]
+            roles: Vec<String>,
+        }
+
+        let claims = json!({"sub": "auth0|123", "aud": ["a", "b"], "exp": 1700000000, "permissions": ["read:orders"]});
+        let mut registered = RegisteredClaims::try_from(claims.clone()).unwrap();
+        assert_eq!(registered.exp, Some(NumericDate(1700000000)));
+        assert_eq!(Claims::from(registered.clone()), claims);
+
+        registered.merge_custom(&Roles { roles: vec!["admin".to_string()] }).unwrap();
+        assert_eq!(registered.custom::<Roles>().unwrap().roles, ["admin"]);
+        registered.merge_custom(&json!({"sub": "auth0|456"})).unwrap();
+        assert_eq!(registered.sub.as_deref(), Some("auth0|456"));
+        assert!(registered.merge_custom(&json!({"exp": "tomorrow"})).is_err());
+        assert!(registered.merge_custom(&json!(["not", "an", "object"])).is_err());
+        assert_eq!(registered.exp, Some(NumericDate(1700000000)));
+
+        assert!(matches!(RegisteredClaims::try_from(json!({"exp": "soon"})).unwrap_err().kind(), ErrorKind::ClaimsDeserialization(_)));
+        assert!(RegisteredClaims::try_from(json!("claims")).is_err());
+    }
+
+    #[test]
+    fn test_profile_claims() {
+        let claims = json!({
+            "given_name": "Jane", "family_name": "Doe", "email": "jane@example.com", "email_verified": true,
+            "picture": "", "name#fr": "Jeanne", "name#pt-BR": "Joana", "locale": "pt_BR",
+        });
+        assert_eq!(claims.name(), None);
+        assert_eq!(claims.display_name(), Some("Jane Doe".to_string()));
+        assert_eq!(claims.email_verified(), Some(true));
+        assert_eq!(claims.picture(), None);
+        assert_eq!(claims.localized("name", claims.locale().unwrap()), Some("Joana"));
+        assert_eq!(claims.localized("name", "FR-ca"), Some("Jeanne"));
+        assert_eq!(claims.localized("name", "de"), None);
+        assert_eq!(json!({"nickname": "jd"}).display_name(), Some("jd".to_string()));
+    }
+
+    #[test]
+    fn test_claims_extract() {
+        let raw = json!({
+            "sub": "auth0|1", "aud": "api", "scope": "read:a write:a", "permissions": ["admin"],
+            "roles": ["ops", 1], "https://example.com/roles": ["billing"], "https://example.com/tier": 3,
+        });
+        let typed: RegisteredClaims = serde_json::from_value(raw.clone()).unwrap();
+        fn check(claims: impl ClaimsExtract) {
+            assert_eq!(claims.subject(), Some("auth0|1".to_string()));
+            assert_eq!(claims.scopes(), vec!["read:a", "write:a", "admin"]);
+            assert!(claims.has_scope("admin"));
+            assert_eq!(claims.roles(), vec!["ops"]);
+            assert_eq!(claims.namespaced_roles("https://example.com/"), vec!["billing"]);
+            assert_eq!(claims.namespaced_claim::<u32>("https://example.com/", "tier"), Some(3));
+            assert_eq!(claims.custom_claim::<String>("tier"), None);
+        }
+        check(&raw);
+        check(Box::new(typed.clone()));
+        check(Arc::new(raw.as_object().unwrap().clone()));
+        assert_eq!(typed.custom_claim::<String>("aud"), Some("api".to_string()));
+        assert_eq!(json!({"sub": ""}).subject(), None);
+    }
+
+    #[test]
+    fn test_audience() {
+        let single: RegisteredClaims = serde_json::from_value(json!({"aud": "api"})).unwrap();
+        assert_eq!(single.aud, Some(Audience::from("api")));
+        assert_eq!(serde_json::to_value(&single).unwrap(), json!({"aud": "api"}));
+        assert_eq!(single.audience(), single.aud);
+
+        let raw = json!({"aud": ["a", 1, "b"]});
+        let audience = raw.audience().unwrap();
+        assert_eq!(audience.iter().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(audience.contains("b") && !audience.contains("c"));
+        assert!(Audience::Multiple(vec![]).is_empty());
+        assert_eq!(json!({"aud": 1}).audience(), None);
+        assert!(serde_json::from_value::<RegisteredClaims>(json!({"aud": 1})).is_err());
+        assert_eq!(Vec::<String>::from(Audience::from("api")), vec!["api".to_string()]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Pluggable time source of the temporal checks.
+//!
+//! Every check comparing a timestamp with the current time (`exp`, `nbf`, the expiry of
+//! x5c certificates, [`CachingHints`](crate::cache::CachingHints)) reads the current time from the
+//! [`Clock`] of the [`Auth0`](crate::Auth0) instance, installed with
+//! [`Auth0::set_clock`](crate::Auth0::set_clock). Platforms with known clock discipline issues,
+//! e.g. VMs stepping their clock after a pause or containers with skew, compensate there once.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of the current time.
+///
+/// Closures `Fn() -> SystemTime` implement it too, e.g. to add a skew measured against a trusted
+/// time server.
+pub trait Clock: Send + Sync {
+    /// The current time.
+    fn now(&self) -> SystemTime;
+
+    /// The current time in seconds since the Unix epoch, zero before the epoch.
+    fn unix_now(&self) -> u64 {
+        self.now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+impl<F> Clock for F
+    where F: Fn() -> SystemTime + Send + Sync
+{
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
+
+/// Default [`Clock`], the system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// [`Clock`] advancing with the monotonic clock from the system time it was last synchronized
+/// at, so steps of the system clock, e.g. by NTP after a VM resumes, never move it backwards.
+///
+/// The monotonic clock may not advance while the host is suspended: call
+/// [`MonotonicClock::resync`] once the system clock is known to be right again.
+///
+/// Example:
+/// ```
+/// use auth0_rs::{Auth0, ValidationOptions};
+/// use auth0_rs::clock::MonotonicClock;
+/// let mut auth0 = Auth0::with_options(r#"{"keys": []}"#, ValidationOptions::default()).unwrap();
+/// auth0.set_clock(MonotonicClock::new());
+/// ```
+#[derive(Debug)]
+pub struct MonotonicClock {
+    anchor: Mutex<(SystemTime, Instant)>,
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        MonotonicClock::new()
+    }
+}
+
+impl MonotonicClock {
+    /// Clock synchronized with the system clock now.
+    pub fn new() -> Self {
+        MonotonicClock::starting_at(SystemTime::now())
+    }
+
+    /// Clock reading `time` now, e.g. a time obtained from a trusted time server.
+    pub fn starting_at(time: SystemTime) -> Self {
+        MonotonicClock { anchor: Mutex::new((time, Instant::now())) }
+    }
+
+    /// Synchronize the clock with the system clock again.
+    pub fn resync(&self) {
+        self.set(SystemTime::now());
+    }
+
+    /// Set the clock to read `time` now.
+    pub fn set(&self, time: SystemTime) {
+        *self.anchor.lock().unwrap() = (time, Instant::now());
+    }
+
+    /// Time elapsed on the monotonic clock since the last synchronization.
+    pub fn since_sync(&self) -> Duration {
+        self.anchor.lock().unwrap().1.elapsed()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> SystemTime {
+        let (time, instant) = *self.anchor.lock().unwrap();
+        time + instant.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::{Algorithm, Auth0};
+    use crate::error::ErrorKind;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_clock() {
+        let clock = MonotonicClock::starting_at(UNIX_EPOCH + Duration::from_secs(1000));
+        assert!((1000..1002).contains(&clock.unix_now()));
+        clock.resync();
+        assert!(clock.now().duration_since(SystemTime::now()).unwrap_or_default() < Duration::from_secs(1));
+
+        let mut auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let exp = test_claims()["exp"].as_u64().unwrap();
+        let skewed = Arc::new(MonotonicClock::starting_at(UNIX_EPOCH + Duration::from_secs(exp - 10)));
+        let source = skewed.clone();
+        auth0.set_clock(move || source.now());
+        assert!(auth0.validate_token(&token).is_ok());
+        let (_, hints) = auth0.validate_token_with_hints(&token).unwrap();
+        assert!(hints.cacheable_for <= Duration::from_secs(10));
+
+        skewed.set(UNIX_EPOCH + Duration::from_secs(exp + 1));
+        assert!(matches!(auth0.validate_token(&token).unwrap_err().kind(), ErrorKind::InvalidToken));
+        auth0.set_clock(SystemClock);
+        assert!(auth0.validate_token(&token).is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+//! Compatibility suite of the deprecated entry points: each must behave exactly like the API
+//! replacing it, so users can upgrade one call at a time, and like the first releases, bar the
+//! changes listed in its deprecation notes.
+#![allow(deprecated)]
+
+use serde_json::json;
+use crate::{Algorithm, Auth0, ValidationOptions, ValidationOverrides};
+use crate::error::ErrorKind;
+use crate::options::EmptyKeySetPolicy;
+use crate::source::JwksSource;
+use crate::testing::{test_jwks, sign_token, test_claims, TEST_KEY_N};
+use crate::test_utils::TestKeyPair;
+
+fn inline(jwks: &str) -> JwksSource {
+    JwksSource::InlineStr(jwks.to_string())
+}
+
+/// Outcome of validating `token`, comparable across instances.
+fn outcome(auth0: &Auth0, token: &str) -> Result<crate::Claims, &'static str> {
+    auth0.validate_token(token).map_err(|e| e.kind().code())
+}
+
+fn keys(auth0: &Auth0) -> serde_json::Value {
+    serde_json::to_value(&auth0.key_map).unwrap()
+}
+
+fn tokens() -> Vec<String> {
+    let mut expired = test_claims();
+    expired["exp"] = json!(1);
+    let other = TestKeyPair::new("other", Algorithm::RS256);
+    vec![
+        sign_token(Algorithm::RS256, &test_claims()),
+        sign_token(Algorithm::RS256, &expired),
+        other.sign(&test_claims()),
+        "not.a.token".to_string(),
+    ]
+}
+
+#[test]
+fn test_new() {
+    let mut legacy = Auth0::new(&test_jwks("RS256")).unwrap();
+    let mut current = Auth0::from_source(inline(&test_jwks("RS256")), ValidationOptions::default()).unwrap();
+    for token in tokens() {
+        assert_eq!(outcome(&legacy, &token), outcome(&current, &token), "{}", token);
+    }
+    assert_eq!(keys(&legacy), keys(&current));
+    assert!(matches!(Auth0::new("{").unwrap_err().kind(), ErrorKind::InvalidJwksStr));
+    assert!(matches!(Auth0::from_source(inline("{"), ValidationOptions::default()).unwrap_err().kind(), ErrorKind::InvalidJwksStr));
+    // instances created from a str have no source to refresh from, unlike those of a source
+    assert!(matches!(legacy.refresh_keys().unwrap_err().kind(), ErrorKind::NoKeySource));
+    current.refresh_keys().unwrap();
+}
+
+#[test]
+fn test_update_keys() {
+    let rotated = TestKeyPair::new("rotated", Algorithm::RS256);
+    let options = ValidationOptions { empty_key_set: EmptyKeySetPolicy::Refuse, ..Default::default() };
+    let mut legacy = Auth0::with_options(&test_jwks("RS256"), options.clone()).unwrap();
+    let mut current = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+
+    legacy.update_keys(&rotated.jwks_string()).unwrap();
+    current.load_source(inline(&rotated.jwks_string())).unwrap();
+    assert_eq!(keys(&legacy), keys(&current));
+    for token in tokens().into_iter().chain([rotated.sign(&test_claims())]) {
+        assert_eq!(outcome(&legacy, &token), outcome(&current, &token));
+    }
+
+    for jwks in ["{", r#"{"keys": []}"#] {
+        let legacy_err = legacy.update_keys(jwks).unwrap_err();
+        let current_err = current.load_source(inline(jwks)).unwrap_err();
+        assert_eq!(legacy_err.kind().code(), current_err.kind().code());
+    }
+    assert!(legacy.key_map.contains_key("rotated"));
+}
+
+#[test]
+fn test_admin_update_keys() {
+    let rotated = TestKeyPair::new("rotated", Algorithm::RS256);
+    let (handle, mut admin) = Auth0::new(&test_jwks("RS256")).unwrap().into_handles();
+    admin.update_keys(&rotated.jwks_string()).unwrap();
+    assert!(handle.validate_token(&rotated.sign(&test_claims())).is_ok());
+    assert!(admin.update_keys("{").is_err());
+    assert!(handle.validate_token(&rotated.sign(&test_claims())).is_ok());
+}
+
+#[test]
+fn test_validate_token() {
+    let auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+    for token in tokens() {
+        let with_defaults = auth0.validate_token_with(&token, &ValidationOverrides::default()).map_err(|e| e.kind().code());
+        assert_eq!(outcome(&auth0, &token), with_defaults);
+    }
+    let claims = auth0.validate_token(&tokens()[0]).unwrap();
+    assert_eq!(claims["aud"], "https://github.com/digizeph/auth0_rs");
+}
+
+/// Outcome of a validation, without the claims.
+type Baseline = Result<(), &'static str>;
+
+#[test]
+fn test_baseline_outcomes() {
+    let jwks = |alg: &str, key_use: &str| json!({"keys": [{"kty": "RSA", "n": TEST_KEY_N, "e": "AQAB", "alg": alg, "kid": "test", "use": key_use}]}).to_string();
+    let key = TestKeyPair::new("test", Algorithm::RS256);
+    let claims = |extra: serde_json::Value| {
+        let mut claims = test_claims();
+        claims.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        claims
+    };
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let (rs256, ps256, enc) = (jwks("RS256", "sig"), jwks("PS256", "sig"), jwks("RS256", "enc"));
+
+    // (case, JWKS, token, outcome of the first releases, outcome now if it changed)
+    let cases: Vec<(&str, &str, String, Baseline, Option<Baseline>)> = vec![
+        ("valid", &rs256, sign_token(Algorithm::RS256, &test_claims()), Ok(()), None),
+        ("expired", &rs256, sign_token(Algorithm::RS256, &claims(json!({"exp": 1}))), Err("invalid_token"), None),
+        ("no exp", &rs256, sign_token(Algorithm::RS256, &json!({"sub": "first-client"})), Err("invalid_token"), None),
+        ("any audience", &rs256, sign_token(Algorithm::RS256, &claims(json!({"aud": "https://other"}))), Ok(()), None),
+        ("unknown kid", &rs256, TestKeyPair::new("other", Algorithm::RS256).sign(&test_claims()), Err("no_match_key"), None),
+        ("no kid", &rs256, key.sign_with_raw_header(&json!({"alg": "RS256"}), &test_claims()), Err("token_missing_key_id"), None),
+        ("garbage", &rs256, "not.a.token".to_string(), Err("invalid_token"), None),
+        ("encryption key", &enc, sign_token(Algorithm::RS256, &test_claims()), Ok(()), None),
+        // the changes listed in the deprecation notes
+        ("future nbf", &rs256, sign_token(Algorithm::RS256, &claims(json!({"nbf": now + 600}))), Ok(()), Some(Err("invalid_token"))),
+        ("RS256 token, PS256 key", &ps256, sign_token(Algorithm::RS256, &test_claims()), Ok(()), Some(Err("algorithm_mismatch"))),
+        ("PS256 token, PS256 key", &ps256, sign_token(Algorithm::PS256, &test_claims()), Err("invalid_token"), Some(Ok(()))),
+        ("PS256 token, RS256 key", &rs256, sign_token(Algorithm::PS256, &test_claims()), Err("invalid_token"), Some(Err("algorithm_mismatch"))),
+    ];
+    for (case, jwks, token, baseline, changed) in cases {
+        let expected = changed.unwrap_or(baseline);
+        let created = Auth0::new(jwks).unwrap();
+        let mut updated = Auth0::new(r#"{"keys": []}"#).unwrap();
+        updated.update_keys(jwks).unwrap();
+        assert_eq!(outcome(&created, &token).map(|_| ()), expected, "{}", case);
+        assert_eq!(outcome(&updated, &token).map(|_| ()), expected, "{}", case);
+    }
+    assert_eq!(Auth0::new("{").map(|_| ()).unwrap_err().kind().code(), "invalid_jwks_str");
+    assert_eq!(Auth0::new(r#"{"keys": [{"kid": "test"}]}"#).map(|_| ()).unwrap_err().kind().code(), "invalid_jwks_str");
+}
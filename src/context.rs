@@ -0,0 +1,57 @@
+//! Per-request context correlating validation decisions with the requests they were made for.
+
+use std::cell::RefCell;
+use std::net::IpAddr;
+
+/// Request a token is validated for, passed to [`Auth0::validate_token_with_context`](crate::Auth0::validate_token_with_context).
+///
+/// For the duration of the validation, the context is forwarded to the hooks of the validator:
+/// warning handlers set with
+/// [`Auth0::set_warning_handler_with_context`](crate::Auth0::set_warning_handler_with_context),
+/// [`ClaimsStage::apply_with_context`](crate::pipeline::ClaimsStage::apply_with_context),
+/// [`ThrottlePolicy`](crate::throttle::ThrottlePolicy) methods and, with the `otel` feature, the
+/// validation span.
+///
+/// Example:
+/// ```
+/// use auth0_rs::context::ValidationContext;
+/// let context = ValidationContext {
+///     request_id: Some("req-42".to_string()),
+///     remote_ip: "203.0.113.7".parse().ok(),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationContext {
+    /// Identifier of the request, e.g. the value of an `X-Request-Id` header.
+    pub request_id: Option<String>,
+    /// Address of the client.
+    pub remote_ip: Option<IpAddr>,
+    /// Route of the request, e.g. `/orders/42`.
+    pub route: Option<String>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<ValidationContext>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `context` as the current context of this thread.
+pub(crate) fn scope<R, F: FnOnce() -> R>(context: &ValidationContext, f: F) -> R {
+    struct Restore(Option<ValidationContext>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(context.clone()))));
+    f()
+}
+
+/// Run `f` with the current context of this thread, if a validation with context is running.
+pub(crate) fn with_current<R, F: FnOnce(Option<&ValidationContext>) -> R>(f: F) -> R {
+    // cloned so hooks may themselves validate tokens
+    let context = CURRENT.with(|current| current.borrow().clone());
+    f(context.as_ref())
+}
@@ -0,0 +1,190 @@
+//! Pluggable signature verification.
+//!
+//! Besides [`RingBackend`], the `aws-lc` feature provides `AwsLcBackend` and the `openssl`
+//! feature `OpenSslBackend`; the enabled feature also selects the [`default_backend`].
+
+use ring::signature::{self, RsaParameters, RsaPublicKeyComponents};
+use crate::{Algorithm, JsonWebKey};
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Backend performing the actual signature verification of tokens.
+///
+/// The default is selected by the cargo features, see [`default_backend`]. Implement this trait
+/// to verify with another library or a hardware module, then install it with
+/// [`Auth0::set_crypto_backend`](crate::Auth0::set_crypto_backend).
+///
+/// Only the RSA algorithms accepted by [`Auth0`](crate::Auth0) are ever requested.
+pub trait CryptoBackend: Send + Sync {
+    /// Whether `signature` (raw bytes, base64url decoded) is a valid `algorithm` signature of
+    /// `message` (the ASCII `header.payload` segments) by the public key `key`.
+    ///
+    /// Return an error only when the key itself is unusable; a wrong signature is `Ok(false)`.
+    fn verify(&self, algorithm: Algorithm, key: &JsonWebKey, message: &[u8], signature: &[u8]) -> Result<bool, Auth0Error>;
+}
+
+/// Default [`CryptoBackend`], based on ring.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RingBackend;
+
+impl CryptoBackend for RingBackend {
+    fn verify(&self, algorithm: Algorithm, key: &JsonWebKey, message: &[u8], signature: &[u8]) -> Result<bool, Auth0Error> {
+        let params: &RsaParameters = match algorithm {
+            Algorithm::RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+            Algorithm::RS384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+            Algorithm::RS512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+            Algorithm::PS256 => &signature::RSA_PSS_2048_8192_SHA256,
+            Algorithm::PS384 => &signature::RSA_PSS_2048_8192_SHA384,
+            Algorithm::PS512 => &signature::RSA_PSS_2048_8192_SHA512,
+            _ => return Err(new_error(ErrorKind::AlgorithmNotAllowed)),
+        };
+        let n = decode_component(&key.n)?;
+        let e = decode_component(&key.e)?;
+        Ok(RsaPublicKeyComponents { n: &n, e: &e }.verify(params, message, signature).is_ok())
+    }
+}
+
+/// [`CryptoBackend`] based on aws-lc-rs, whose `fips` feature links the FIPS-validated AWS-LC
+/// module. Requires the `aws-lc` feature.
+#[cfg(feature = "aws-lc")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsLcBackend;
+
+#[cfg(feature = "aws-lc")]
+impl CryptoBackend for AwsLcBackend {
+    fn verify(&self, algorithm: Algorithm, key: &JsonWebKey, message: &[u8], signature: &[u8]) -> Result<bool, Auth0Error> {
+        use aws_lc_rs::signature::{self, RsaParameters, RsaPublicKeyComponents};
+
+        let params: &RsaParameters = match algorithm {
+            Algorithm::RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+            Algorithm::RS384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+            Algorithm::RS512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+            Algorithm::PS256 => &signature::RSA_PSS_2048_8192_SHA256,
+            Algorithm::PS384 => &signature::RSA_PSS_2048_8192_SHA384,
+            Algorithm::PS512 => &signature::RSA_PSS_2048_8192_SHA512,
+            _ => return Err(new_error(ErrorKind::AlgorithmNotAllowed)),
+        };
+        let n = decode_component(&key.n)?;
+        let e = decode_component(&key.e)?;
+        Ok(RsaPublicKeyComponents { n: &n, e: &e }.verify(params, message, signature).is_ok())
+    }
+}
+
+/// [`CryptoBackend`] based on the OpenSSL library of the system, e.g. a FIPS provider configured
+/// there. Requires the `openssl` feature.
+///
+/// Like the other backends, it only accepts RSA keys of 2048 to 8192 bits.
+#[cfg(feature = "openssl")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenSslBackend;
+
+#[cfg(feature = "openssl")]
+impl CryptoBackend for OpenSslBackend {
+    fn verify(&self, algorithm: Algorithm, key: &JsonWebKey, message: &[u8], signature: &[u8]) -> Result<bool, Auth0Error> {
+        use openssl::bn::BigNum;
+        use openssl::hash::MessageDigest;
+        use openssl::pkey::PKey;
+        use openssl::rsa::{Padding, Rsa};
+        use openssl::sign::{RsaPssSaltlen, Verifier};
+
+        let (digest, pss) = match algorithm {
+            Algorithm::RS256 => (MessageDigest::sha256(), false),
+            Algorithm::RS384 => (MessageDigest::sha384(), false),
+            Algorithm::RS512 => (MessageDigest::sha512(), false),
+            Algorithm::PS256 => (MessageDigest::sha256(), true),
+            Algorithm::PS384 => (MessageDigest::sha384(), true),
+            Algorithm::PS512 => (MessageDigest::sha512(), true),
+            _ => return Err(new_error(ErrorKind::AlgorithmNotAllowed)),
+        };
+        let unusable = |_| new_error(ErrorKind::InvalidJwksStr);
+        let n = BigNum::from_slice(&decode_component(&key.n)?).map_err(unusable)?;
+        let e = BigNum::from_slice(&decode_component(&key.e)?).map_err(unusable)?;
+        if !(2048..=8192).contains(&n.num_bits()) {
+            return Ok(false)
+        }
+        let key = PKey::from_rsa(Rsa::from_public_components(n, e).map_err(unusable)?).map_err(unusable)?;
+        let verified = (|| {
+            let mut verifier = Verifier::new(digest, &key)?;
+            if pss {
+                verifier.set_rsa_padding(Padding::PKCS1_PSS)?;
+                verifier.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                verifier.set_rsa_mgf1_md(digest)?;
+            }
+            verifier.update(message)?;
+            verifier.verify(signature)
+        })();
+        Ok(verified.unwrap_or(false))
+    }
+}
+
+/// The [`CryptoBackend`] of new [`Auth0`](crate::Auth0) instances and of signed JWKS
+/// verification: `AwsLcBackend` with the `aws-lc` feature, else `OpenSslBackend` with the
+/// `openssl` feature, else [`RingBackend`].
+pub fn default_backend() -> Box<dyn CryptoBackend> {
+    #[cfg(feature = "aws-lc")]
+    let backend = AwsLcBackend;
+    #[cfg(all(feature = "openssl", not(feature = "aws-lc")))]
+    let backend = OpenSslBackend;
+    #[cfg(not(any(feature = "aws-lc", feature = "openssl")))]
+    let backend = RingBackend;
+    Box::new(backend)
+}
+
+fn decode_component(component: &str) -> Result<Vec<u8>, Auth0Error> {
+    base64::decode_config(component, base64::URL_SAFE_NO_PAD).map_err(|_| new_error(ErrorKind::InvalidJwksStr))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::Auth0;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use crate::test_utils::TestKeyPair;
+    use super::*;
+
+    struct Counting(Arc<AtomicUsize>);
+
+    impl CryptoBackend for Counting {
+        fn verify(&self, algorithm: Algorithm, key: &JsonWebKey, message: &[u8], signature: &[u8]) -> Result<bool, Auth0Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            RingBackend.verify(algorithm, key, message, signature)
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_crypto_backend() {
+        let token = sign_token(Algorithm::PS384, &test_claims());
+        let mut auth0 = Auth0::new(&test_jwks("PS384")).unwrap();
+        assert!(auth0.validate_token(&token).is_ok());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        auth0.set_crypto_backend(Counting(calls.clone()));
+        assert!(auth0.validate_token(&token).is_ok());
+        assert!(auth0.validate_token(&format!("{}A", token)).is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_backends() {
+        #[allow(unused_mut)]
+        let mut backends: Vec<(&str, Box<dyn CryptoBackend>)> = vec![("ring", Box::new(RingBackend))];
+        #[cfg(feature = "aws-lc")]
+        backends.push(("aws-lc", Box::new(AwsLcBackend)));
+        #[cfg(feature = "openssl")]
+        backends.push(("openssl", Box::new(OpenSslBackend)));
+
+        for alg in [Algorithm::RS256, Algorithm::RS384, Algorithm::RS512, Algorithm::PS256, Algorithm::PS384, Algorithm::PS512] {
+            let key = TestKeyPair::new("test", alg);
+            let token = key.sign(&test_claims());
+            let (message, signature) = token.rsplit_once('.').unwrap();
+            let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD).unwrap();
+            for (name, backend) in &backends {
+                assert!(backend.verify(alg, &key.jwk(), message.as_bytes(), &signature).unwrap(), "{} {:?}", name, alg);
+                assert!(!backend.verify(alg, &key.jwk(), b"tampered", &signature).unwrap(), "{} {:?}", name, alg);
+                assert!(!backend.verify(alg, &key.jwk(), message.as_bytes(), &signature[1..]).unwrap(), "{} {:?}", name, alg);
+                assert!(matches!(backend.verify(Algorithm::HS256, &key.jwk(), message.as_bytes(), &signature).unwrap_err().kind(), ErrorKind::AlgorithmNotAllowed));
+            }
+        }
+    }
+}
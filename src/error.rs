@@ -1,3 +1,12 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+use crate::Algorithm;
+use serde::Serialize;
+
 /// An error that can occur when encoding/decoding JWTs
 #[derive(Debug)]
 pub struct Auth0Error(Box<ErrorKind>);
@@ -17,9 +26,26 @@ impl Auth0Error {
     pub fn into_kind(self) -> ErrorKind {
         *self.0
     }
+
+    /// Build the serializable [`ErrorResponse`] view of this error.
+    pub fn to_response(&self) -> ErrorResponse {
+        ErrorResponse::from(self)
+    }
 }
 
+impl fmt::Display for Auth0Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.kind(), f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Auth0Error {}
+
+/// Kind of an [`Auth0Error`]. New variants are added in minor releases, so matches need a
+/// wildcard arm; see [`ErrorKind::category`] for a stable grouping.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Invalid token
     InvalidToken,
@@ -29,4 +55,505 @@ pub enum ErrorKind {
     NoMatchKey,
     /// Invalid JSON web key set string
     InvalidJwksStr,
-}
\ No newline at end of file
+    /// Token signing algorithm is not allowed for the matching key
+    AlgorithmNotAllowed,
+    /// The matching key is not meant for signature verification (`use` / `key_ops`)
+    IneligibleKey,
+    /// Token audience (`aud`) does not match the expected audience
+    InvalidAudience,
+    /// Invalid JSON Schema document for claims
+    InvalidClaimsSchema,
+    /// Token claims violate the configured JSON Schema
+    ClaimsSchemaViolation(Vec<SchemaViolation>),
+    /// Request does not carry a bearer token
+    MissingBearerToken,
+    /// Token issuer (`iss`) does not match the expected issuer
+    InvalidIssuer {
+        /// The accepted issuer; several accepted issuers are joined for display
+        expected: String,
+        /// The token's issuer, `None` if the claim is missing
+        actual: Option<String>,
+    },
+    /// Token does not carry a required scope
+    MissingScope(String),
+    /// Invalid x5c certificate
+    InvalidCertificate,
+    /// The x5c certificate of the key with the given key ID is expired
+    CertificateExpired(String),
+    /// Downloading keys failed
+    FetchFailed {
+        /// The requested URL
+        url: String,
+        /// Reason of the failure
+        reason: String,
+    },
+    /// A JWKS URL is configured without a fetcher to download it
+    NoFetcher,
+    /// No key source is configured
+    NoKeySource,
+    /// Two key sources define the key with the given key ID with different key material
+    KeySourceConflict(String),
+    /// Too many failed validations for the caller, see [`ThrottlePolicy`](crate::throttle::ThrottlePolicy)
+    Throttled,
+    /// Cryptographic self-test of a loaded key failed
+    SelfTestFailed {
+        /// Key ID of the failing key
+        kid: String,
+        /// Reason of the failure
+        reason: String,
+    },
+    /// Token does not carry a session ID (`sid`) although one is required
+    MissingSessionId,
+    /// Logout token violates the OIDC Back-Channel Logout rules, for the given reason
+    InvalidLogoutToken(&'static str),
+    /// Token, or one of its header and payload segments, is larger than the configured limit
+    TokenTooLarge {
+        /// The oversized part: `token`, `header` or `payload`
+        part: &'static str,
+        /// Observed size in bytes
+        size: usize,
+        /// Configured limit in bytes
+        limit: usize,
+    },
+    /// Token signing algorithm differs from the algorithm pinned by the matching key's `alg`
+    AlgorithmMismatch {
+        /// Key ID of the matching key
+        kid: String,
+        /// The algorithm declared by the key
+        expected: Algorithm,
+        /// The algorithm declared by the token
+        actual: Algorithm,
+    },
+    /// Invalid public key returned by a key provider, for the given reason
+    InvalidPublicKey(String),
+    /// The JWKS file at `path` cannot be read
+    JwksFileUnreadable {
+        /// Path of the file
+        path: String,
+        /// Reason of the failure
+        reason: String,
+    },
+    /// A network-backed operation did not complete within the given budget
+    Timeout(Duration),
+    /// The key set is empty, refused by [`EmptyKeySetPolicy::Refuse`](crate::options::EmptyKeySetPolicy::Refuse)
+    EmptyKeySet,
+    /// Invalid or unsupported validation policy document, for the given reason
+    InvalidPolicy(String),
+    /// A token segment contains base64 padding, rejected by [`ValidationOptions::strict_base64`](crate::ValidationOptions::strict_base64)
+    Base64Padding(&'static str),
+    /// A token segment contains a character outside the base64url alphabet, rejected by [`ValidationOptions::strict_base64`](crate::ValidationOptions::strict_base64)
+    Base64InvalidCharacter {
+        /// The segment: `header`, `payload` or `signature`
+        segment: &'static str,
+        /// Byte offset of the character in the segment
+        position: usize,
+    },
+    /// A token segment has trailing bits or a length no canonical base64url encoding produces, rejected by [`ValidationOptions::strict_base64`](crate::ValidationOptions::strict_base64)
+    Base64TrailingBits(&'static str),
+    /// A downloaded JWKS document failed the signature verification against the trust anchor, for the given reason
+    UntrustedJwks(String),
+    /// The token header lists in `crit` an extension not registered in [`ValidationOptions::critical_extensions`](crate::ValidationOptions::critical_extensions) (RFC 7515 section 4.1.11)
+    UnsupportedCriticalExtension(String),
+    /// The token nests signed JWTs (`cty: JWT`) deeper than [`ValidationOptions::max_nesting_depth`](crate::ValidationOptions::max_nesting_depth) allows
+    NestingTooDeep(usize),
+    /// The header `typ` of the token, given if present, is not one of [`ValidationOptions::token_types`](crate::ValidationOptions::token_types)
+    InvalidTokenType(Option<String>),
+    /// The token lacks a claim listed in [`ValidationOptions::required_claims`](crate::ValidationOptions::required_claims)
+    MissingClaim(String),
+    /// The `azp` claim of an ID token is missing although it has several audiences, or is not the expected client ID (OIDC Core section 3.1.3.7)
+    InvalidAuthorizedParty(Option<String>),
+    /// Contradictory or dangerous settings given to [`Auth0Builder::build`](crate::builder::Auth0Builder::build), for the given reason
+    InvalidConfiguration(String),
+    /// Malformed [`ClaimsAssertion`](crate::assertion::ClaimsAssertion) expression, for the given reason
+    InvalidAssertion(String),
+    /// The claims do not satisfy the given assertion of [`ValidationOptions::assertions`](crate::ValidationOptions::assertions)
+    AssertionFailed(String),
+    /// The keys could not be refreshed for the given time, longer than [`StaleKeyPolicy::max_staleness`](crate::options::StaleKeyPolicy::max_staleness) allows on the route
+    StaleKeys(Duration),
+    /// The claims do not deserialize into the requested type for the given reason, e.g. in [`Auth0::validate_token_as`](crate::Auth0::validate_token_as)
+    ClaimsDeserialization(String),
+    /// The token was issued after an authentication weaker than [`ValidationOptions::authentication_strength`](crate::ValidationOptions::authentication_strength) requires
+    InsufficientAuthentication(String),
+}
+
+impl ErrorKind {
+    /// Stable machine-readable code of the error, e.g. `invalid_token`.
+    ///
+    /// Codes never change once released, so they are safe to match on in API clients.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::InvalidToken => "invalid_token",
+            ErrorKind::TokenMissingKeyId => "token_missing_key_id",
+            ErrorKind::NoMatchKey => "no_match_key",
+            ErrorKind::InvalidJwksStr => "invalid_jwks_str",
+            ErrorKind::AlgorithmNotAllowed => "algorithm_not_allowed",
+            ErrorKind::IneligibleKey => "ineligible_key",
+            ErrorKind::InvalidAudience => "invalid_audience",
+            ErrorKind::InvalidClaimsSchema => "invalid_claims_schema",
+            ErrorKind::ClaimsSchemaViolation(_) => "claims_schema_violation",
+            ErrorKind::MissingBearerToken => "missing_bearer_token",
+            ErrorKind::InvalidIssuer { .. } => "invalid_issuer",
+            ErrorKind::MissingScope(_) => "missing_scope",
+            ErrorKind::InvalidCertificate => "invalid_certificate",
+            ErrorKind::CertificateExpired(_) => "certificate_expired",
+            ErrorKind::FetchFailed { .. } => "fetch_failed",
+            ErrorKind::NoFetcher => "no_fetcher",
+            ErrorKind::NoKeySource => "no_key_source",
+            ErrorKind::KeySourceConflict(_) => "key_source_conflict",
+            ErrorKind::Throttled => "throttled",
+            ErrorKind::SelfTestFailed { .. } => "self_test_failed",
+            ErrorKind::MissingSessionId => "missing_session_id",
+            ErrorKind::InvalidLogoutToken(_) => "invalid_logout_token",
+            ErrorKind::TokenTooLarge { .. } => "token_too_large",
+            ErrorKind::AlgorithmMismatch { .. } => "algorithm_mismatch",
+            ErrorKind::InvalidPublicKey(_) => "invalid_public_key",
+            ErrorKind::JwksFileUnreadable { .. } => "jwks_file_unreadable",
+            ErrorKind::Timeout(_) => "timeout",
+            ErrorKind::EmptyKeySet => "empty_key_set",
+            ErrorKind::InvalidPolicy(_) => "invalid_policy",
+            ErrorKind::Base64Padding(_) => "base64_padding",
+            ErrorKind::Base64InvalidCharacter { .. } => "base64_invalid_character",
+            ErrorKind::Base64TrailingBits(_) => "base64_trailing_bits",
+            ErrorKind::UntrustedJwks(_) => "untrusted_jwks",
+            ErrorKind::UnsupportedCriticalExtension(_) => "unsupported_critical_extension",
+            ErrorKind::NestingTooDeep(_) => "nesting_too_deep",
+            ErrorKind::InvalidTokenType(_) => "invalid_token_type",
+            ErrorKind::MissingClaim(_) => "missing_claim",
+            ErrorKind::InvalidAuthorizedParty(_) => "invalid_authorized_party",
+            ErrorKind::InvalidConfiguration(_) => "invalid_configuration",
+            ErrorKind::InvalidAssertion(_) => "invalid_assertion",
+            ErrorKind::AssertionFailed(_) => "assertion_failed",
+            ErrorKind::StaleKeys(_) => "stale_keys",
+            ErrorKind::ClaimsDeserialization(_) => "claims_deserialization",
+            ErrorKind::InsufficientAuthentication(_) => "insufficient_user_authentication",
+        }
+    }
+
+    /// Category of the error, e.g. to pick the HTTP status of the response without matching
+    /// every variant.
+    ///
+    /// Example:
+    /// ```
+    /// use auth0_rs::error::{ErrorCategory, ErrorKind};
+    /// assert_eq!(ErrorKind::MissingScope("read:users".to_string()).category(), ErrorCategory::Forbidden);
+    /// assert_eq!(ErrorKind::NoMatchKey.category().status_code(), 401);
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ErrorKind::InvalidToken
+            | ErrorKind::TokenMissingKeyId
+            | ErrorKind::NoMatchKey
+            | ErrorKind::AlgorithmNotAllowed
+            | ErrorKind::IneligibleKey
+            | ErrorKind::InvalidAudience
+            | ErrorKind::ClaimsSchemaViolation(_)
+            | ErrorKind::MissingBearerToken
+            | ErrorKind::InvalidIssuer { .. }
+            | ErrorKind::InvalidCertificate
+            | ErrorKind::CertificateExpired(_)
+            | ErrorKind::MissingSessionId
+            | ErrorKind::InvalidLogoutToken(_)
+            | ErrorKind::TokenTooLarge { .. }
+            | ErrorKind::AlgorithmMismatch { .. }
+            | ErrorKind::Base64Padding(_)
+            | ErrorKind::Base64InvalidCharacter { .. }
+            | ErrorKind::Base64TrailingBits(_)
+            | ErrorKind::UnsupportedCriticalExtension(_)
+            | ErrorKind::NestingTooDeep(_)
+            | ErrorKind::InvalidTokenType(_)
+            | ErrorKind::MissingClaim(_)
+            | ErrorKind::InvalidAuthorizedParty(_) => ErrorCategory::Unauthorized,
+            ErrorKind::MissingScope(_)
+            | ErrorKind::AssertionFailed(_)
+            | ErrorKind::InsufficientAuthentication(_) => ErrorCategory::Forbidden,
+            ErrorKind::InvalidJwksStr
+            | ErrorKind::InvalidClaimsSchema
+            | ErrorKind::NoFetcher
+            | ErrorKind::NoKeySource
+            | ErrorKind::KeySourceConflict(_)
+            | ErrorKind::SelfTestFailed { .. }
+            | ErrorKind::InvalidPublicKey(_)
+            | ErrorKind::JwksFileUnreadable { .. }
+            | ErrorKind::EmptyKeySet
+            | ErrorKind::InvalidPolicy(_)
+            | ErrorKind::UntrustedJwks(_)
+            | ErrorKind::InvalidConfiguration(_)
+            | ErrorKind::InvalidAssertion(_)
+            | ErrorKind::ClaimsDeserialization(_) => ErrorCategory::Misconfiguration,
+            ErrorKind::FetchFailed { .. }
+            | ErrorKind::Throttled
+            | ErrorKind::Timeout(_)
+            | ErrorKind::StaleKeys(_) => ErrorCategory::Transient,
+        }
+    }
+
+    /// Optional extra detail about the error, for variants carrying data.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            ErrorKind::ClaimsSchemaViolation(violations) => Some(
+                violations.iter()
+                    .map(|v| format!("{}: {}", v.path, v.message))
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Coarse classification of [`ErrorKind`]s, see [`ErrorKind::category`].
+///
+/// New error variants are added to one of these categories, so code matching on categories
+/// keeps working across releases.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// The request carries no valid token: missing, malformed, badly signed, expired or issued
+    /// for another audience or issuer.
+    Unauthorized,
+    /// The token is valid but does not grant the request, e.g. a missing scope.
+    Forbidden,
+    /// The validator is misconfigured, e.g. invalid keys or settings, or a validated token does
+    /// not fit the claims type of the application; retrying does not help.
+    Misconfiguration,
+    /// Temporary failure, e.g. an unavailable JWKS endpoint; retrying later may succeed.
+    Transient,
+}
+
+impl ErrorCategory {
+    /// Conventional HTTP status of the category: `401`, `403`, `500` or `503`.
+    pub fn status_code(self) -> u16 {
+        match self {
+            ErrorCategory::Unauthorized => 401,
+            ErrorCategory::Forbidden => 403,
+            ErrorCategory::Misconfiguration => 500,
+            ErrorCategory::Transient => 503,
+        }
+    }
+}
+
+/// A single JSON Schema violation found in token claims.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending claim, e.g. `/plan`.
+    pub path: String,
+    /// Description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            ErrorKind::InvalidToken => "invalid token",
+            ErrorKind::TokenMissingKeyId => "token does not contain a key ID (kid)",
+            ErrorKind::NoMatchKey => "no matching key in the JSON web key set",
+            ErrorKind::InvalidJwksStr => "invalid JSON web key set",
+            ErrorKind::AlgorithmNotAllowed => "token signing algorithm is not allowed",
+            ErrorKind::IneligibleKey => "matching key is not eligible for signature verification",
+            ErrorKind::InvalidAudience => "token audience does not match the expected audience",
+            ErrorKind::InvalidClaimsSchema => "invalid claims JSON schema",
+            ErrorKind::ClaimsSchemaViolation(_) => "token claims violate the claims schema",
+            ErrorKind::MissingBearerToken => "request does not carry a bearer token",
+            ErrorKind::InvalidIssuer { expected, actual } => {
+                return match actual {
+                    Some(actual) => write!(f, "token issuer does not match: expected `{}`, found `{}`", expected, actual),
+                    None => write!(f, "token issuer does not match: expected `{}`, found no issuer", expected),
+                }
+            }
+            ErrorKind::MissingScope(scope) => {
+                return write!(f, "token does not carry required scope `{}`", scope)
+            }
+            ErrorKind::InvalidCertificate => "invalid x5c certificate",
+            ErrorKind::CertificateExpired(kid) => {
+                return write!(f, "certificate of key `{}` is expired", kid)
+            }
+            ErrorKind::FetchFailed { url, reason } => {
+                return write!(f, "fetching `{}` failed: {}", url, reason)
+            }
+            ErrorKind::NoFetcher => "a JWKS URL is configured without a fetcher",
+            ErrorKind::NoKeySource => "no key source is configured",
+            ErrorKind::KeySourceConflict(kid) => {
+                return write!(f, "key sources define key `{}` with different key material", kid)
+            }
+            ErrorKind::Throttled => "too many failed validations",
+            ErrorKind::SelfTestFailed { kid, reason } => {
+                return write!(f, "self-test of key `{}` failed: {}", kid, reason)
+            }
+            ErrorKind::MissingSessionId => "token does not carry a session ID (sid)",
+            ErrorKind::InvalidLogoutToken(reason) => {
+                return write!(f, "invalid logout token: {}", reason)
+            }
+            ErrorKind::TokenTooLarge { part, size, limit } => {
+                return write!(f, "{} of {} bytes exceeds the limit of {} bytes", part, size, limit)
+            }
+            ErrorKind::AlgorithmMismatch { kid, expected, actual } => {
+                return write!(f, "token algorithm {:?} does not match algorithm {:?} of key `{}`", actual, expected, kid)
+            }
+            ErrorKind::InvalidPublicKey(reason) => {
+                return write!(f, "invalid public key: {}", reason)
+            }
+            ErrorKind::JwksFileUnreadable { path, reason } => {
+                return write!(f, "reading JWKS file `{}` failed: {}", path, reason)
+            }
+            ErrorKind::Timeout(budget) => {
+                return write!(f, "operation did not complete within {} ms", budget.as_millis())
+            }
+            ErrorKind::EmptyKeySet => "the JSON web key set contains no keys",
+            ErrorKind::InvalidPolicy(reason) => {
+                return write!(f, "invalid validation policy: {}", reason)
+            }
+            ErrorKind::Base64Padding(segment) => {
+                return write!(f, "token {} contains base64 padding", segment)
+            }
+            ErrorKind::Base64InvalidCharacter { segment, position } => {
+                return write!(f, "token {} contains a non-base64url character at offset {}", segment, position)
+            }
+            ErrorKind::Base64TrailingBits(segment) => {
+                return write!(f, "token {} is not canonically base64url encoded", segment)
+            }
+            ErrorKind::UntrustedJwks(reason) => {
+                return write!(f, "JWKS signature verification failed: {}", reason)
+            }
+            ErrorKind::UnsupportedCriticalExtension(name) => {
+                return write!(f, "unsupported critical header extension `{}`", name)
+            }
+            ErrorKind::NestingTooDeep(max) => {
+                return write!(f, "token nests JWTs deeper than the maximum of {}", max)
+            }
+            ErrorKind::InvalidTokenType(typ) => {
+                return match typ {
+                    Some(typ) => write!(f, "token type `{}` is not accepted", typ),
+                    None => f.write_str("token type (typ) is missing"),
+                }
+            }
+            ErrorKind::MissingClaim(name) => {
+                return write!(f, "token is missing the required claim `{}`", name)
+            }
+            ErrorKind::InvalidAuthorizedParty(azp) => {
+                return match azp {
+                    Some(azp) => write!(f, "token authorized party `{}` is not the expected client", azp),
+                    None => f.write_str("token with several audiences is missing its authorized party (azp)"),
+                }
+            }
+            ErrorKind::InvalidConfiguration(reason) => {
+                return write!(f, "invalid configuration: {}", reason)
+            }
+            ErrorKind::InvalidAssertion(reason) => {
+                return write!(f, "invalid claims assertion {}", reason)
+            }
+            ErrorKind::AssertionFailed(assertion) => {
+                return write!(f, "claims do not satisfy the assertion `{}`", assertion)
+            }
+            ErrorKind::StaleKeys(staleness) => {
+                return write!(f, "keys could not be refreshed for {} s", staleness.as_secs())
+            }
+            ErrorKind::ClaimsDeserialization(reason) => {
+                return write!(f, "claims do not deserialize into the requested type: {}", reason)
+            }
+            ErrorKind::InsufficientAuthentication(reason) => {
+                return write!(f, "insufficient user authentication: {}", reason)
+            }
+        };
+        f.write_str(message)
+    }
+}
+
+/// Serializable view of an [`Auth0Error`], meant to be returned as a JSON error body.
+///
+/// Example output:
+/// ```json
+/// {"code": "no_match_key", "message": "no matching key in the JSON web key set"}
+/// ```
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ErrorResponse {
+    /// Stable machine-readable error code, see [`ErrorKind::code`].
+    pub code: &'static str,
+    /// Human-readable error message.
+    pub message: String,
+    /// Optional extra detail about the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl From<&Auth0Error> for ErrorResponse {
+    fn from(error: &Auth0Error) -> Self {
+        let kind = error.kind();
+        ErrorResponse {
+            code: kind.code(),
+            message: kind.to_string(),
+            detail: kind.detail(),
+        }
+    }
+}
+
+impl From<Auth0Error> for ErrorResponse {
+    fn from(error: Auth0Error) -> Self {
+        ErrorResponse::from(&error)
+    }
+}
+
+/// Every claim check a token failed, see
+/// [`Auth0::validate_token_report`](crate::Auth0::validate_token_report).
+#[derive(Debug)]
+pub struct ValidationReport {
+    /// The failures, in validation order.
+    pub failures: Vec<CheckFailure>,
+}
+
+/// A single failure of a [`ValidationReport`].
+#[derive(Debug)]
+pub struct CheckFailure {
+    /// Name of the failed check: `token` for failures preventing any claim check (malformed
+    /// token, unknown key, bad signature...), else `exp`, `nbf`, `aud`, `iss`, `scope`, `sid`,
+    /// `authentication`, `claims`, `assertion` or `schema`.
+    pub check: &'static str,
+    /// The error of the check.
+    pub error: Auth0Error,
+}
+
+impl ValidationReport {
+    /// Serializable views of the failures, meant to be returned as a JSON error body.
+    pub fn to_responses(&self) -> Vec<ErrorResponse> {
+        self.failures.iter().map(|failure| ErrorResponse::from(&failure.error)).collect()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}: {}", failure.check, failure.error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationReport {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_response() {
+        let response = new_error(ErrorKind::NoMatchKey).to_response();
+        assert_eq!(response.code, "no_match_key");
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"code":"no_match_key","message":"no matching key in the JSON web key set"}"#
+        );
+    }
+
+    #[test]
+    fn test_error_category() {
+        assert_eq!(ErrorKind::InvalidAudience.category(), ErrorCategory::Unauthorized);
+        assert_eq!(ErrorKind::AssertionFailed("sub != null".into()).category().status_code(), 403);
+        assert_eq!(ErrorKind::NoKeySource.category().status_code(), 500);
+        assert_eq!(ErrorKind::ClaimsDeserialization("missing field `tenant`".into()).category().status_code(), 500);
+        assert_eq!(ErrorKind::Timeout(Duration::from_secs(1)).category(), ErrorCategory::Transient);
+        assert_eq!(serde_json::to_string(&ErrorCategory::Misconfiguration).unwrap(), r#""misconfiguration""#);
+    }
+}
@@ -0,0 +1,116 @@
+//! Internal notifications of key set changes, letting caches drop only the entries tied to keys
+//! that were removed or changed instead of everything.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use crate::JsonWebKey;
+use crate::source::same_key_material;
+
+/// Change of a single key between two key sets.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum KeyChange {
+    /// A key with a new key ID.
+    Added(String),
+    /// A key that is no longer in the key set.
+    Removed(String),
+    /// A key ID now bound to different key material or algorithm.
+    Modified(String),
+}
+
+impl KeyChange {
+    /// Key ID of the changed key.
+    pub(crate) fn kid(&self) -> &str {
+        match self {
+            KeyChange::Added(kid) | KeyChange::Removed(kid) | KeyChange::Modified(kid) => kid,
+        }
+    }
+
+    /// Whether results computed with the previous key of this ID are no longer valid.
+    pub(crate) fn invalidates(&self) -> bool {
+        !matches!(self, KeyChange::Added(_))
+    }
+}
+
+/// Changes turning the key set `old` into `new`, ordered by key ID.
+pub(crate) fn diff(old: &HashMap<String, JsonWebKey>, new: &HashMap<String, JsonWebKey>) -> Vec<KeyChange> {
+    let mut changes: Vec<KeyChange> = old.iter()
+        .filter_map(|(kid, key)| match new.get(kid) {
+            None => Some(KeyChange::Removed(kid.clone())),
+            Some(new_key) if !same_key_material(key, new_key) => Some(KeyChange::Modified(kid.clone())),
+            Some(_) => None,
+        })
+        .chain(new.keys().filter(|kid| !old.contains_key(*kid)).map(|kid| KeyChange::Added(kid.clone())))
+        .collect();
+    changes.sort_by(|a, b| a.kid().cmp(b.kid()));
+    changes
+}
+
+/// Receiver of [`KeyChange`]s, typically a cache.
+pub(crate) trait KeyChangeSubscriber: Send + Sync {
+    /// Handle one change.
+    fn key_changed(&self, change: &KeyChange);
+}
+
+/// Dispatcher of [`KeyChange`]s to subscribers.
+///
+/// Subscribers are held weakly: dropping a cache is enough to unsubscribe it.
+#[derive(Default)]
+pub(crate) struct KeyEventBus {
+    subscribers: Mutex<Vec<Weak<dyn KeyChangeSubscriber>>>,
+}
+
+impl KeyEventBus {
+    /// Deliver future changes to `subscriber`.
+    pub(crate) fn subscribe(&self, subscriber: Arc<dyn KeyChangeSubscriber>) {
+        self.subscribers.lock().unwrap().push(Arc::downgrade(&subscriber));
+    }
+
+    /// Deliver `changes` to every live subscriber.
+    pub(crate) fn publish(&self, changes: &[KeyChange]) {
+        if changes.is_empty() {
+            return
+        }
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| subscriber.strong_count() > 0);
+        for subscriber in subscribers.iter().filter_map(|subscriber| subscriber.upgrade()) {
+            for change in changes {
+                subscriber.key_changed(change);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Jwks;
+    use crate::testing::test_jwks;
+    use super::*;
+
+    struct Recorder(Mutex<Vec<KeyChange>>);
+
+    impl KeyChangeSubscriber for Recorder {
+        fn key_changed(&self, change: &KeyChange) {
+            self.0.lock().unwrap().push(change.clone());
+        }
+    }
+
+    #[test]
+    fn test_key_events() {
+        let keymap = |alg: &str| crate::Auth0::jwks_to_keymap(Jwks::parse(&test_jwks(alg)).unwrap());
+        let mut new = keymap("PS256");
+        let mut added = new["test"].clone();
+        added.kid = "added".to_string();
+        new.insert("added".to_string(), added);
+        assert_eq!(diff(&keymap("RS256"), &new), vec![KeyChange::Added("added".to_string()), KeyChange::Modified("test".to_string())]);
+        assert_eq!(diff(&new, &HashMap::new()).iter().filter(|change| change.invalidates()).count(), 2);
+
+        let bus = KeyEventBus::default();
+        let recorder = Arc::new(Recorder(Mutex::new(vec![])));
+        bus.subscribe(recorder.clone());
+        bus.publish(&[KeyChange::Removed("test".to_string())]);
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+        drop(recorder);
+        bus.publish(&[KeyChange::Removed("test".to_string())]);
+        assert!(bus.subscribers.lock().unwrap().is_empty());
+    }
+}
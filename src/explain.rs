@@ -0,0 +1,192 @@
+//! Diagnosis of rejected tokens for support workflows.
+
+use std::fmt;
+use jsonwebtoken::decode_header;
+use serde::Serialize;
+use crate::{expected_algorithm, Auth0, Claims};
+use crate::claims::{Audience, ClaimsExt, ClaimsExtract};
+use crate::audit::{describe_authentication_strength, describe_not_before};
+use crate::validation::{accepted_issuers, check_authentication_strength, check_not_before, matched_issuer, required_scopes, token_scopes};
+
+/// Outcome of a single check reported by [`explain_failure`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CheckOutcome {
+    /// Name of the check: `format`, `kid`, `alg`, `signature`, `exp`, `nbf`, `aud`, `iss`,
+    /// `scope`, `sid` or `authentication`.
+    pub check: &'static str,
+    /// Whether the token passes the check.
+    pub passed: bool,
+    /// What the configuration expects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// What the token carries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+}
+
+/// Report of the checks a token passes and fails, see [`explain_failure`].
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct FailureExplanation {
+    /// Every check performed, in validation order.
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl FailureExplanation {
+    /// The failed checks.
+    pub fn failures(&self) -> impl Iterator<Item = &CheckOutcome> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+
+    /// Whether every check passed.
+    pub fn is_clean(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    fn push(&mut self, check: &'static str, passed: bool, expected: Option<String>, actual: Option<String>) {
+        self.checks.push(CheckOutcome { check, passed, expected, actual });
+    }
+}
+
+impl fmt::Display for FailureExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.checks {
+            write!(f, "{} {}", if outcome.passed { "ok  " } else { "FAIL" }, outcome.check)?;
+            if let Some(expected) = &outcome.expected {
+                write!(f, ": expected {}", expected)?;
+            }
+            if let Some(actual) = &outcome.actual {
+                write!(f, ", found {}", actual)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Explain which of the checks configured on `auth0` a token fails, and why.
+///
+/// The payload is decoded without asserting validity, so every check is reported even when an
+/// earlier one fails, with the expected and actual values (e.g. of `aud`, `iss` or `exp`). Meant
+/// for "why is my token rejected" support workflows: never trust the claims of a token based on
+/// this report.
+///
+/// Example:
+/// ```ignore
+/// let explanation = auth0_rs::explain::explain_failure(token, &auth0);
+/// for failure in explanation.failures() {
+///     eprintln!("{:?}", failure);
+/// }
+/// ```
+pub fn explain_failure(token: &str, auth0: &Auth0) -> FailureExplanation {
+    let mut explanation = FailureExplanation::default();
+    let options = auth0.options();
+
+    let header = decode_header(token).ok();
+    let claims = decode_payload(token);
+    let (header, claims) = match (header, claims) {
+        (Some(header), Some(claims)) => (header, claims),
+        _ => {
+            explanation.push("format", false, Some("a JWT with a JSON header and payload".to_string()), None);
+            return explanation
+        }
+    };
+    explanation.push("format", true, None, None);
+
+    let mut key_ids: Vec<&str> = auth0.key_map.keys().map(|k| k.as_str()).collect();
+    key_ids.sort_unstable();
+    let key = header.kid.as_ref().and_then(|kid| auth0.key_map.get(kid));
+    explanation.push("kid", key.is_some(), Some(format!("one of {:?}", key_ids)), header.kid.clone());
+
+    if let Some(key) = key {
+        let algorithm = expected_algorithm(key, header.alg, options);
+        let expected = if options.algorithms.is_empty() { key.alg.clone() } else { format!("one of {:?}", options.algorithms) };
+        explanation.push("alg", algorithm.is_ok(), Some(expected), Some(format!("{:?}", header.alg)));
+        if let Ok(algorithm) = algorithm {
+            let valid = auth0.verify_signature_with_key(token, &key.kid, key, algorithm).is_ok();
+            explanation.push("signature", valid, None, None);
+        }
+    }
+
+    let now = auth0.clock.unix_now();
+    let earliest = now.saturating_sub(options.leeway.saturating_add(options.expired_grace.as_secs()));
+    let exp = claims.get("exp").and_then(|v| v.as_u64());
+    explanation.push("exp", exp.map(|exp| exp >= earliest).unwrap_or(false),
+                     Some(format!("at least {} (now {})", earliest, now)), exp.map(|exp| exp.to_string()));
+    if let Some((expected, actual)) = describe_not_before(&claims, options, now) {
+        explanation.push("nbf", check_not_before(&claims, options, now).is_ok(), expected, actual);
+    }
+
+    if let Some(audience) = &options.audience {
+        let actual = claims.audience().map(Audience::into_vec).unwrap_or_default();
+        let passed = actual.iter().any(|aud| audience.contains(aud));
+        explanation.push("aud", passed, Some(format!("one of {:?}", audience)), Some(format!("{:?}", actual)));
+    }
+
+    let issuers: Vec<&str> = accepted_issuers(options).collect();
+    if !issuers.is_empty() {
+        let actual = claims.get("iss").and_then(|v| v.as_str());
+        let passed = matched_issuer(&claims, options).is_some();
+        explanation.push("iss", passed, Some(issuers.join(" or ")), actual.map(|iss| iss.to_string()));
+    }
+
+    let scopes = token_scopes(&claims);
+    for required in required_scopes(&claims, options) {
+        explanation.push("scope", scopes.contains(&required.as_str()), Some(required.clone()), Some(scopes.join(" ")));
+    }
+
+    if options.require_session_id {
+        let sid = claims.session_id().map(|sid| sid.to_string());
+        explanation.push("sid", sid.is_some(), Some("a session ID".to_string()), sid);
+    }
+
+    if let Some((expected, actual)) = describe_authentication_strength(&claims, options) {
+        let passed = check_authentication_strength(&claims, options).is_ok();
+        explanation.push("authentication", passed, expected, actual);
+    }
+
+    explanation
+}
+
+/// Decode the payload of a token without verifying anything.
+fn decode_payload(token: &str) -> Option<Claims> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()?;
+    serde_json::from_slice::<Claims>(&bytes).ok().filter(|claims| claims.is_object())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{Algorithm, ValidationOptions};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    fn test_explain_failure() {
+        let options = ValidationOptions {
+            audience: Some(vec!["https://api.example.com".to_string()]),
+            issuer: Some("https://tenant.auth0.com/".to_string()),
+            ..Default::default()
+        };
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let mut claims = test_claims();
+        claims["iss"] = json!("https://tenant.auth0.com");
+        let explanation = explain_failure(&sign_token(Algorithm::RS256, &claims), &auth0);
+
+        let failures: Vec<&CheckOutcome> = explanation.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].check, "aud");
+        assert_eq!(failures[0].actual.as_deref(), Some(r#"["https://github.com/digizeph/auth0_rs"]"#));
+        assert!(explanation.to_string().contains("FAIL aud"));
+
+        let explanation = explain_failure("not a token", &auth0);
+        assert_eq!(explanation.checks[0].check, "format");
+        assert!(!explanation.is_clean());
+
+        // any expired token is accepted
+        let options = ValidationOptions { expired_grace: std::time::Duration::MAX, leeway: u64::MAX, ..Default::default() };
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let explanation = explain_failure(&sign_token(Algorithm::RS256, &json!({"sub": "first-client", "exp": 1})), &auth0);
+        assert!(explanation.is_clean());
+    }
+}
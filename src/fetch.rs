@@ -0,0 +1,112 @@
+use std::sync::mpsc;
+use std::time::Instant;
+use crate::Jwks;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Response of a [`Fetcher`] request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response body.
+    pub body: String,
+}
+
+/// Blocking HTTP client used to download JWKS documents and to call token introspection endpoints.
+///
+/// The crate does not pick an HTTP stack for you: implement this trait on top of the client your
+/// application already uses. Closures `Fn(&str) -> Result<FetchResponse, Auth0Error>` implement it too.
+pub trait Fetcher: Send + Sync {
+    /// Perform a `GET` request.
+    fn get(&self, url: &str) -> Result<FetchResponse, Auth0Error>;
+
+    /// Perform a `POST` request with an `application/x-www-form-urlencoded` body made of `form`,
+    /// as used by [`IntrospectionClient`](crate::introspection::IntrospectionClient).
+    ///
+    /// The default implementation fails: fetchers only downloading keys need not implement it.
+    fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<FetchResponse, Auth0Error> {
+        let _ = form;
+        Err(new_error(ErrorKind::FetchFailed {
+            url: url.to_string(),
+            reason: "fetcher does not support POST requests".to_string(),
+        }))
+    }
+}
+
+impl<F> Fetcher for F
+    where F: Fn(&str) -> Result<FetchResponse, Auth0Error> + Send + Sync
+{
+    fn get(&self, url: &str) -> Result<FetchResponse, Auth0Error> {
+        self(url)
+    }
+}
+
+/// Download and parse a JWKS document.
+pub fn fetch_jwks(fetcher: &dyn Fetcher, url: &str) -> Result<Jwks, Auth0Error> {
+    Jwks::parse(&fetch_body(fetcher, url)?)
+}
+
+/// Download a document, failing unless the response status is `200`.
+pub(crate) fn fetch_body(fetcher: &dyn Fetcher, url: &str) -> Result<String, Auth0Error> {
+    response_body(url, fetcher.get(url)?)
+}
+
+/// Body of a successful response to a request to `url`.
+pub(crate) fn response_body(url: &str, response: FetchResponse) -> Result<String, Auth0Error> {
+    if response.status != 200 {
+        return Err(new_error(ErrorKind::FetchFailed {
+            url: url.to_string(),
+            reason: format!("unexpected status {}", response.status),
+        }))
+    }
+    Ok(response.body)
+}
+
+/// Run the blocking, typically network-bound, `operation` on a separate thread, failing with
+/// [`ErrorKind::Timeout`] if it has not completed by `deadline`. A timed out operation keeps
+/// running in the background and its result is dropped.
+pub(crate) fn run_with_deadline<T, F>(deadline: Instant, operation: F) -> Result<T, Auth0Error>
+    where T: Send + 'static, F: FnOnce() -> Result<T, Auth0Error> + Send + 'static
+{
+    let budget = deadline.saturating_duration_since(Instant::now());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(operation());
+    });
+    match receiver.recv_timeout(budget) {
+        Ok(result) => result,
+        Err(_) => Err(new_error(ErrorKind::Timeout(budget))),
+    }
+}
+
+/// Encode `form` as an `application/x-www-form-urlencoded` body, for [`Fetcher::post_form`]
+/// implementations.
+pub fn encode_form(form: &[(&str, &str)]) -> String {
+    form.iter()
+        .map(|(name, value)| format!("{}={}", form_urlencode(name), form_urlencode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn form_urlencode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => encoded.push(byte as char),
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_form() {
+        assert_eq!(encode_form(&[("token", "a.b_c"), ("client_secret", "s3cr+t/ &=")]),
+                   "token=a.b_c&client_secret=s3cr%2Bt%2F+%26%3D");
+    }
+}
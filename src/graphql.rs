@@ -0,0 +1,200 @@
+//! async-graphql integration, enabled by the `async-graphql` feature.
+//!
+//! The HTTP layer validates the bearer token once per request with [`authenticate_request`],
+//! which stores the outcome in the request data; resolvers read the [`Principal`] with
+//! [`principal`] and fields are protected with the [`RequireAuth`] and [`RequireScope`] guards.
+//!
+//! Example:
+//! ```ignore
+//! use auth0_rs::graphql::{authenticate_request, RequireScope};
+//!
+//! #[Object]
+//! impl Query {
+//!     #[graphql(guard = "RequireScope::new(\"read:orders\")")]
+//!     async fn orders(&self) -> Vec<Order> { ... }
+//! }
+//!
+//! // in the HTTP handler
+//! let request = authenticate_request(request, &auth0, headers.get("authorization").and_then(|v| v.to_str().ok()));
+//! schema.execute(request).await
+//! ```
+
+use async_graphql::{Context, ErrorExtensions, Guard, Request};
+use crate::{Auth0, Claims};
+use crate::bearer::parse_authorization;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::validation::token_scopes;
+
+/// The caller authenticated by a validated token, available in the GraphQL context.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// Subject of the token (`sub`).
+    pub subject: Option<String>,
+    /// Scopes of the token: its `scope` claim plus its `permissions` (Auth0 RBAC).
+    pub scopes: Vec<String>,
+    /// All claims of the validated token.
+    pub claims: Claims,
+}
+
+impl Principal {
+    /// Principal of validated claims.
+    pub fn from_claims(claims: Claims) -> Principal {
+        let subject = claims.get("sub").and_then(|v| v.as_str()).map(|s| s.to_string());
+        let scopes = token_scopes(&claims).into_iter().map(|s| s.to_string()).collect();
+        Principal { subject, scopes, claims }
+    }
+
+    /// Whether the token carries `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// Why no [`Principal`] is available, stored in the request data by [`authenticate_request`]
+/// when the token is missing or invalid.
+#[derive(Debug)]
+pub struct AuthenticationFailure(pub Auth0Error);
+
+/// Validate the bearer token of an `Authorization` header value.
+pub fn authenticate(auth0: &Auth0, authorization: Option<&str>) -> Result<Principal, Auth0Error> {
+    let token = authorization
+        .and_then(parse_authorization)
+        .ok_or_else(|| new_error(ErrorKind::MissingBearerToken))?;
+    auth0.validate_token(token).map(Principal::from_claims)
+}
+
+/// Validate the bearer token of an `Authorization` header value and store the [`Principal`], or
+/// the [`AuthenticationFailure`], in the request data.
+///
+/// A failure does not reject the request, so unguarded fields stay publicly accessible.
+pub fn authenticate_request(request: Request, auth0: &Auth0, authorization: Option<&str>) -> Request {
+    match authenticate(auth0, authorization) {
+        Ok(principal) => request.data(principal),
+        Err(error) => request.data(AuthenticationFailure(error)),
+    }
+}
+
+/// The authenticated caller of the request, if any.
+pub fn principal<'a>(ctx: &Context<'a>) -> Option<&'a Principal> {
+    ctx.data_opt::<Principal>()
+}
+
+/// Principal of the request, or the GraphQL error explaining why there is none.
+fn require_principal<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Principal> {
+    if let Some(principal) = principal(ctx) {
+        return Ok(principal)
+    }
+    match ctx.data_opt::<AuthenticationFailure>() {
+        Some(failure) => Err(graphql_error(&failure.0)),
+        None => Err(graphql_error(&new_error(ErrorKind::MissingBearerToken))),
+    }
+}
+
+/// GraphQL error carrying the error [`code`](ErrorKind::code) as the `code` extension.
+fn graphql_error(error: &Auth0Error) -> async_graphql::Error {
+    let code = error.kind().code();
+    async_graphql::Error::new(error.to_string()).extend_with(|_, e| e.set("code", code))
+}
+
+/// Guard allowing any authenticated caller.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequireAuth;
+
+impl Guard for RequireAuth {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        require_principal(ctx).map(|_| ())
+    }
+}
+
+/// Guard allowing authenticated callers whose token carries every given scope.
+#[derive(Debug, Clone)]
+pub struct RequireScope {
+    scopes: Vec<String>,
+}
+
+impl RequireScope {
+    /// Require a single scope, e.g. `read:orders`.
+    pub fn new(scope: &str) -> RequireScope {
+        RequireScope { scopes: vec![scope.to_string()] }
+    }
+
+    /// Require every scope of `scopes`.
+    pub fn all(scopes: &[&str]) -> RequireScope {
+        RequireScope { scopes: scopes.iter().map(|s| s.to_string()).collect() }
+    }
+}
+
+impl Guard for RequireScope {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let principal = require_principal(ctx)?;
+        match self.scopes.iter().find(|scope| !principal.has_scope(scope)) {
+            Some(missing) => Err(graphql_error(&new_error(ErrorKind::MissingScope(missing.clone())))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::task::{Context as TaskContext, Poll, Waker};
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+    use serde_json::json;
+    use super::*;
+    use crate::Algorithm;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn public(&self) -> &str {
+            "hello"
+        }
+
+        #[graphql(guard = "RequireAuth")]
+        async fn me(&self, ctx: &Context<'_>) -> Option<String> {
+            principal(ctx).and_then(|p| p.subject.clone())
+        }
+
+        #[graphql(guard = "RequireScope::new(\"read:orders\")")]
+        async fn orders(&self) -> i32 {
+            3
+        }
+    }
+
+    fn execute(auth0: &Auth0, authorization: Option<&str>, query: &str) -> async_graphql::Response {
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        let request = authenticate_request(Request::new(query), auth0, authorization);
+        let mut future = Box::pin(schema.execute(request));
+        let mut cx = TaskContext::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(response) = future.as_mut().poll(&mut cx) {
+                return response
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_guards() {
+        let auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let mut claims = test_claims();
+        claims["permissions"] = json!(["read:orders"]);
+        let with_scope = format!("Bearer {}", sign_token(Algorithm::RS256, &claims));
+        let without_scope = format!("Bearer {}", sign_token(Algorithm::RS256, &test_claims()));
+
+        let response = execute(&auth0, None, "{ public }");
+        assert!(response.errors.is_empty());
+        let response = execute(&auth0, None, "{ me }");
+        assert_eq!(response.errors[0].extensions.as_ref().unwrap().get("code"), Some(&"missing_bearer_token".into()));
+
+        let response = execute(&auth0, Some(&without_scope), "{ me orders }");
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(response.errors[0].extensions.as_ref().unwrap().get("code"), Some(&"missing_scope".into()));
+
+        let response = execute(&auth0, Some(&with_scope), "{ me orders }");
+        assert!(response.errors.is_empty());
+        assert_eq!(response.data.into_json().unwrap(), json!({"me": "first-client", "orders": 3}));
+    }
+}
@@ -0,0 +1,185 @@
+//! Shared handles separating token validation from key and policy administration.
+//!
+//! [`Auth0::into_handles`] moves a validator behind a lock and returns an [`Auth0Handle`], cheap
+//! to clone into every request handler and only able to validate, and a single [`Auth0Admin`]
+//! through which key updates and policy swaps go. Validations run concurrently; an update waits
+//! for the running validations and is seen by every later one.
+
+use std::sync::{Arc, RwLock};
+use crate::{Auth0, Claims, JsonWebKey, ValidationOptions, ValidationOverrides};
+use crate::error::{Auth0Error, ValidationReport};
+use crate::health::KeyHealth;
+use crate::source::{JwksSource, SourceStats};
+use crate::policy::ValidationPolicy;
+
+/// Validation side of a shared [`Auth0`], see the [module documentation](self).
+///
+/// Example:
+/// ```
+/// # let jwks = include_str!("../testdata/jwks.json");
+/// use auth0_rs::{Auth0, ValidationOptions};
+/// use auth0_rs::source::JwksSource;
+/// let source = || JwksSource::InlineStr(jwks.to_string());
+/// let (handle, mut admin) = Auth0::from_source(source(), ValidationOptions::default()).unwrap().into_handles();
+/// let worker = handle.clone();
+/// std::thread::spawn(move || worker.validate_token("token").is_ok()).join().unwrap();
+/// admin.load_source(source()).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Auth0Handle {
+    auth0: Arc<RwLock<Auth0>>,
+}
+
+impl Auth0Handle {
+    /// See [`Auth0::validate_token`].
+    pub fn validate_token(&self, token: &str) -> Result<Claims, Auth0Error> {
+        self.with(|auth0| auth0.validate_token(token))
+    }
+
+    /// See [`Auth0::validate_token_for_audience`].
+    pub fn validate_token_for_audience(&self, token: &str, audience: &str) -> Result<Claims, Auth0Error> {
+        self.with(|auth0| auth0.validate_token_for_audience(token, audience))
+    }
+
+    /// See [`Auth0::validate_token_with`].
+    pub fn validate_token_with(&self, token: &str, overrides: &ValidationOverrides) -> Result<Claims, Auth0Error> {
+        self.with(|auth0| auth0.validate_token_with(token, overrides))
+    }
+
+    /// See [`Auth0::validate_token_report`].
+    pub fn validate_token_report(&self, token: &str) -> Result<Claims, ValidationReport> {
+        self.with(|auth0| auth0.validate_token_report(token))
+    }
+
+    /// See [`Auth0::key_health`].
+    pub fn key_health(&self) -> KeyHealth {
+        self.with(|auth0| auth0.key_health())
+    }
+
+    /// See [`Auth0::source_stats`].
+    pub fn source_stats(&self) -> Vec<SourceStats> {
+        self.with(|auth0| auth0.source_stats())
+    }
+
+    /// See [`Auth0::verify_signature`].
+    pub fn verify_signature(&self, token: &str) -> Result<bool, Auth0Error> {
+        self.with(|auth0| auth0.verify_signature(token))
+    }
+
+    /// Run `f` with read access to the validator, e.g. for the less common validation methods.
+    ///
+    /// Key updates wait for `f` to return: keep it short.
+    pub fn with<R, F: FnOnce(&Auth0) -> R>(&self, f: F) -> R {
+        f(&self.auth0.read().unwrap())
+    }
+}
+
+/// Administration side of a shared [`Auth0`], see the [module documentation](self).
+///
+/// There is one admin per validator: it is not [`Clone`], so ownership of updates stays explicit.
+pub struct Auth0Admin {
+    auth0: Arc<RwLock<Auth0>>,
+}
+
+impl Auth0Admin {
+    /// A new handle validating with this validator.
+    pub fn handle(&self) -> Auth0Handle {
+        Auth0Handle { auth0: self.auth0.clone() }
+    }
+
+    /// See [`Auth0::update_keys`].
+    #[deprecated(note = "use `Auth0Admin::load_source(JwksSource::InlineStr(..))`; unlike the first releases, a future `nbf` is rejected and the token `alg` must match its key's `alg`")]
+    pub fn update_keys(&mut self, jwks_str: &str) -> Result<(), Auth0Error> {
+        self.load_source(JwksSource::InlineStr(jwks_str.to_string()))
+    }
+
+    /// See [`Auth0::load_source`].
+    pub fn load_source(&mut self, source: JwksSource) -> Result<(), Auth0Error> {
+        self.modify(|auth0| auth0.load_source(source))
+    }
+
+    /// See [`Auth0::set_source`].
+    pub fn set_source(&mut self, source: JwksSource) -> Result<(), Auth0Error> {
+        self.modify(|auth0| auth0.set_source(source))
+    }
+
+    /// See [`Auth0::refresh_keys`]. The keys are loaded before taking the lock, so validations
+    /// are not blocked by the download.
+    pub fn refresh_keys(&mut self) -> Result<(), Auth0Error> {
+        let sources = self.auth0.read().unwrap().sources.clone();
+        let loaded = match sources {
+            Some(sources) => sources.load(),
+            None => return self.modify(|auth0| auth0.refresh_keys()),
+        };
+        self.modify(|auth0| auth0.install_refreshed_keys(loaded))
+    }
+
+    /// See [`Auth0::add_key`].
+    pub fn add_key(&mut self, key: JsonWebKey) -> Result<(), Auth0Error> {
+        self.modify(|auth0| auth0.add_key(key))
+    }
+
+    /// See [`Auth0::remove_key`].
+    pub fn remove_key(&mut self, kid: &str) -> Result<JsonWebKey, Auth0Error> {
+        self.modify(|auth0| auth0.remove_key(kid))
+    }
+
+    /// See [`Auth0::merge_jwks`].
+    pub fn merge_jwks(&mut self, jwks_str: &str) -> Result<(), Auth0Error> {
+        self.modify(|auth0| auth0.merge_jwks(jwks_str))
+    }
+
+    /// See [`Auth0::policy`].
+    pub fn policy(&self) -> ValidationPolicy {
+        self.auth0.read().unwrap().policy()
+    }
+
+    /// See [`Auth0::set_policy`].
+    pub fn set_policy(&mut self, policy: ValidationPolicy) -> Result<ValidationPolicy, Auth0Error> {
+        self.modify(|auth0| auth0.set_policy(policy))
+    }
+
+    /// See [`Auth0::set_options`].
+    pub fn set_options(&mut self, options: ValidationOptions) {
+        self.modify(|auth0| auth0.set_options(options))
+    }
+
+    /// Run `f` with write access to the validator, blocking validations until it returns.
+    pub fn modify<R, F: FnOnce(&mut Auth0) -> R>(&mut self, f: F) -> R {
+        f(&mut self.auth0.write().unwrap())
+    }
+}
+
+impl Auth0 {
+    /// Share the validator between request handlers, see the [`handle`](crate::handle) module.
+    pub fn into_handles(self) -> (Auth0Handle, Auth0Admin) {
+        let auth0 = Arc::new(RwLock::new(self));
+        (Auth0Handle { auth0: auth0.clone() }, Auth0Admin { auth0 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::test_utils::{RotatingTestTenant, short_lived_claims};
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_handles() {
+        let tenant = RotatingTestTenant::new();
+        let (handle, mut admin) = Auth0::new(&tenant.v1().jwks_string()).unwrap().into_handles();
+        let claims = short_lived_claims(3600);
+        let v2_token = tenant.v2().sign(&claims);
+
+        let worker = admin.handle();
+        let token = tenant.v1().sign(&claims);
+        assert!(thread::spawn(move || worker.validate_token(&token).is_ok()).join().unwrap());
+        assert!(handle.validate_token(&v2_token).is_err());
+
+        admin.add_key(tenant.v2().jwk()).unwrap();
+        assert!(handle.clone().validate_token(&v2_token).is_ok());
+        assert_eq!(handle.with(|auth0| auth0.key_map.len()), 2);
+        assert!(admin.refresh_keys().is_err());
+    }
+}
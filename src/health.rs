@@ -0,0 +1,175 @@
+//! Freshness of the loaded keys, for health checks and readiness probes.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use crate::options::StaleKeyPolicy;
+
+/// Freshness of the loaded keys, see [`StaleKeyPolicy`].
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyFreshness {
+    /// The last key load succeeded.
+    Fresh,
+    /// The last refresh failed; the keys are still used.
+    Stale,
+    /// Refreshes have been failing for longer than [`StaleKeyPolicy::max_staleness`]; tokens are
+    /// only validated on the [`StaleKeyPolicy::fail_open_routes`].
+    Expired,
+}
+
+/// Health of the loaded keys, returned by [`Auth0::key_health`](crate::Auth0::key_health).
+///
+/// Transitions between [`KeyFreshness`] states are also reported to the warning handler as
+/// [`ValidationWarning::KeyFreshnessChanged`](crate::ValidationWarning::KeyFreshnessChanged).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct KeyHealth {
+    /// Freshness of the keys.
+    pub freshness: KeyFreshness,
+    /// Time of the last successful key load.
+    pub last_refresh: SystemTime,
+    /// Time elapsed since `last_refresh` while refreshes are failing, zero when fresh.
+    pub staleness: Duration,
+    /// Number of refreshes failed since `last_refresh`.
+    pub consecutive_failures: u32,
+    /// Display of the error of the last failed refresh, since `last_refresh`.
+    pub last_error: Option<String>,
+}
+
+/// Outcomes of the key refreshes of an [`Auth0`](crate::Auth0) instance.
+pub(crate) struct RefreshTracker {
+    state: Mutex<RefreshState>,
+}
+
+struct RefreshState {
+    last_refresh: SystemTime,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    reported: KeyFreshness,
+}
+
+impl RefreshTracker {
+    /// Tracker of keys loaded now.
+    pub(crate) fn new() -> RefreshTracker {
+        RefreshTracker {
+            state: Mutex::new(RefreshState {
+                last_refresh: SystemTime::now(),
+                consecutive_failures: 0,
+                last_error: None,
+                reported: KeyFreshness::Fresh,
+            }),
+        }
+    }
+
+    /// Record a successful key load.
+    pub(crate) fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.last_refresh = SystemTime::now();
+        state.consecutive_failures = 0;
+        state.last_error = None;
+    }
+
+    /// Record a failed refresh.
+    pub(crate) fn record_failure(&self, error: String) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.last_error = Some(error);
+    }
+
+    /// Current health under `policy`, and the freshness it had when last observed if that
+    /// changed since.
+    pub(crate) fn observe(&self, policy: &StaleKeyPolicy) -> (KeyHealth, Option<KeyFreshness>) {
+        let mut state = self.state.lock().unwrap();
+        let staleness = match state.consecutive_failures {
+            0 => Duration::ZERO,
+            _ => SystemTime::now().duration_since(state.last_refresh).unwrap_or_default(),
+        };
+        let freshness = match state.consecutive_failures {
+            0 => KeyFreshness::Fresh,
+            _ if policy.max_staleness.is_some_and(|max| staleness > max) => KeyFreshness::Expired,
+            _ => KeyFreshness::Stale,
+        };
+        let previous = std::mem::replace(&mut state.reported, freshness);
+        let health = KeyHealth {
+            freshness,
+            last_refresh: state.last_refresh,
+            staleness,
+            consecutive_failures: state.consecutive_failures,
+            last_error: state.last_error.clone(),
+        };
+        (health, Some(previous).filter(|previous| *previous != freshness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_tracker() {
+        let tracker = RefreshTracker::new();
+        let policy = StaleKeyPolicy { max_staleness: Some(Duration::ZERO), ..Default::default() };
+        assert_eq!(tracker.observe(&policy).0.freshness, KeyFreshness::Fresh);
+
+        tracker.record_failure("down".to_string());
+        let (health, previous) = tracker.observe(&StaleKeyPolicy::default());
+        assert_eq!((health.freshness, previous), (KeyFreshness::Stale, Some(KeyFreshness::Fresh)));
+        assert_eq!((health.consecutive_failures, health.last_error.as_deref()), (1, Some("down")));
+        std::thread::sleep(Duration::from_millis(5));
+        let (health, previous) = tracker.observe(&policy);
+        assert_eq!((health.freshness, previous), (KeyFreshness::Expired, Some(KeyFreshness::Stale)));
+        assert_eq!(tracker.observe(&policy).1, None);
+
+        tracker.record_success();
+        let (health, previous) = tracker.observe(&policy);
+        assert_eq!((health.freshness, health.staleness, previous), (KeyFreshness::Fresh, Duration::ZERO, Some(KeyFreshness::Expired)));
+    }
+
+    #[test]
+    fn test_stale_key_policy() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use crate::{ValidationOptions, ValidationWarning};
+        use crate::builder::Auth0Builder;
+        use crate::context::ValidationContext;
+        use crate::error::{new_error, ErrorKind};
+        use crate::fetch::FetchResponse;
+        use crate::test_utils::{TestKeyPair, short_lived_claims};
+
+        let key = TestKeyPair::new("test", crate::Algorithm::RS256);
+        let (jwks, down) = (key.jwks_string(), Arc::new(AtomicBool::new(false)));
+        let outage = down.clone();
+        let fetcher = move |url: &str| match outage.load(Ordering::SeqCst) {
+            true => Err(new_error(ErrorKind::FetchFailed { url: url.to_string(), reason: "down".to_string() })),
+            false => Ok(FetchResponse { status: 200, body: jwks.clone() }),
+        };
+        let options = ValidationOptions {
+            stale_keys: StaleKeyPolicy { max_staleness: Some(Duration::from_millis(1)), fail_open_routes: vec!["/public/".to_string()] },
+            ..Default::default()
+        };
+        let mut auth0 = Auth0Builder::new().jwks_url("https://tenant/jwks.json").fetcher(fetcher).options(options).build().unwrap();
+        let warnings = Arc::new(Mutex::new(vec![]));
+        let sink = warnings.clone();
+        auth0.set_warning_handler(move |warning| sink.lock().unwrap().push(warning.clone()));
+        let token = key.sign(&short_lived_claims(3600));
+
+        down.store(true, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(auth0.refresh_keys().is_err());
+        let err = auth0.validate_token(&token).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::StaleKeys(_)));
+        let public = ValidationContext { route: Some("/public/docs".to_string()), ..Default::default() };
+        assert!(auth0.validate_token_with_context(&token, &public).is_ok());
+        assert_eq!(auth0.key_health().consecutive_failures, 1);
+
+        down.store(false, Ordering::SeqCst);
+        auth0.refresh_keys().unwrap();
+        assert!(auth0.validate_token(&token).is_ok());
+        let changes: Vec<(KeyFreshness, KeyFreshness)> = warnings.lock().unwrap().iter().filter_map(|warning| match warning {
+            ValidationWarning::KeyFreshnessChanged { from, to } => Some((*from, *to)),
+            _ => None,
+        }).collect();
+        // the keys were already older than max_staleness when the refresh failed
+        assert_eq!(changes, vec![(KeyFreshness::Fresh, KeyFreshness::Expired), (KeyFreshness::Expired, KeyFreshness::Fresh)]);
+    }
+}
@@ -0,0 +1,184 @@
+//! OAuth 2.0 token introspection (RFC 7662) for opaque tokens that cannot be validated locally.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::claims::Audience;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::fetch::{run_with_deadline, Fetcher};
+use crate::secret::SecretString;
+
+/// Response of an introspection endpoint.
+///
+/// Only `active` is guaranteed; every other field is optional. An inactive token (expired,
+/// revoked or unknown) carries no other information.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct IntrospectionResult {
+    /// Whether the token is currently active.
+    pub active: bool,
+    /// Space-delimited scopes of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Client the token was issued to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Human-readable identifier of the resource owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Type of the token, e.g. `Bearer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// Expiration time, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// Issuance time, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    /// Time before which the token must not be accepted, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// Subject of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audiences of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// Issuer of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Unique identifier of the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Any other field of the response, e.g. vendor specific claims.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl IntrospectionResult {
+    /// Scopes of the token, split from [`scope`](Self::scope).
+    pub fn scopes(&self) -> Vec<&str> {
+        match &self.scope {
+            Some(scope) => scope.split_whitespace().collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// Client of an introspection endpoint, authenticating with client credentials sent in the form
+/// body (`client_secret_post`).
+///
+/// Example:
+/// ```ignore
+/// use auth0_rs::introspection::IntrospectionClient;
+/// let client = IntrospectionClient::new("https://idp.example.com/oauth/introspect", "my-api", "secret", fetcher);
+/// let result = client.introspect(opaque_token)?;
+/// if result.active && result.scopes().contains(&"read:orders") { ... }
+/// ```
+#[derive(Clone)]
+pub struct IntrospectionClient {
+    endpoint: String,
+    client_id: String,
+    client_secret: SecretString,
+    fetcher: Arc<dyn Fetcher>,
+}
+
+impl std::fmt::Debug for IntrospectionClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntrospectionClient")
+            .field("endpoint", &self.endpoint)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IntrospectionClient {
+    /// Client of the introspection endpoint at `endpoint`, sending `POST` requests with `fetcher`
+    /// (see [`Fetcher::post_form`]).
+    ///
+    /// With the `zeroize` feature, every clone of the client wipes its copy of `client_secret`
+    /// from memory when dropped.
+    pub fn new<F: Fetcher + 'static>(endpoint: &str, client_id: &str, client_secret: &str, fetcher: F) -> IntrospectionClient {
+        IntrospectionClient {
+            endpoint: endpoint.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: SecretString::from(client_secret),
+            fetcher: Arc::new(fetcher),
+        }
+    }
+
+    /// Introspect a token.
+    ///
+    /// An inactive token is not an error: check [`IntrospectionResult::active`].
+    pub fn introspect(&self, token: &str) -> Result<IntrospectionResult, Auth0Error> {
+        let form = [
+            ("token", token),
+            ("token_type_hint", "access_token"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        let response = self.fetcher.post_form(&self.endpoint, &form)?;
+        if response.status != 200 {
+            return Err(self.failure(format!("unexpected status {}", response.status)))
+        }
+        serde_json::from_str(&response.body).map_err(|e| self.failure(format!("invalid introspection response: {}", e)))
+    }
+
+    /// Introspect a token, failing with [`ErrorKind::Timeout`] if the endpoint has not answered
+    /// within `timeout`, for latency-bound callers whose [`Fetcher`] has no timeout of its own.
+    pub fn introspect_with_deadline(&self, token: &str, timeout: Duration) -> Result<IntrospectionResult, Auth0Error> {
+        let client = self.clone();
+        let token = SecretString::from(token);
+        run_with_deadline(Instant::now() + timeout, move || client.introspect(token.as_str()))
+    }
+
+    fn failure(&self, reason: String) -> Auth0Error {
+        new_error(ErrorKind::FetchFailed { url: self.endpoint.clone(), reason })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use crate::fetch::{encode_form, FetchResponse};
+    use super::*;
+
+    struct Endpoint(Mutex<Vec<String>>);
+
+    impl Fetcher for Endpoint {
+        fn get(&self, _url: &str) -> Result<FetchResponse, Auth0Error> {
+            unreachable!()
+        }
+
+        fn post_form(&self, _url: &str, form: &[(&str, &str)]) -> Result<FetchResponse, Auth0Error> {
+            self.0.lock().unwrap().push(encode_form(form));
+            if form[0].1 == "slow" {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+            let body = match form[0].1 {
+                "good" => r#"{"active": true, "scope": "read:orders write:orders", "sub": "auth0|1", "aud": "https://api", "exp": 32520059430, "org_id": "acme"}"#,
+                _ => r#"{"active": false}"#,
+            };
+            Ok(FetchResponse { status: 200, body: body.to_string() })
+        }
+    }
+
+    #[test]
+    fn test_introspect() {
+        let client = IntrospectionClient::new("https://idp/introspect", "api", "secret", Endpoint(Mutex::new(vec![])));
+        let result = client.introspect("good").unwrap();
+        assert!(result.active);
+        assert_eq!(result.scopes(), vec!["read:orders", "write:orders"]);
+        assert_eq!(result.aud, Some(Audience::from("https://api")));
+        assert_eq!(result.extra["org_id"], "acme");
+        assert!(!client.introspect("revoked").unwrap().active);
+        assert!(client.introspect_with_deadline("good", Duration::from_secs(5)).unwrap().active);
+        let err = client.introspect_with_deadline("slow", Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Timeout(_)));
+
+        let client = IntrospectionClient::new("https://idp/introspect", "api", "secret",
+            |_: &str| -> Result<FetchResponse, Auth0Error> { unreachable!() });
+        assert!(matches!(client.introspect("good").unwrap_err().kind(), ErrorKind::FetchFailed { .. }));
+    }
+}
@@ -0,0 +1,155 @@
+//! JSON Web Key (set) structures.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::Algorithm;
+
+/// JSON Web Key struct.
+///
+/// Official documentation of the JSON Web Key format from Auth0:
+/// https://auth0.com/docs/tokens/json-web-tokens/json-web-key-set-properties
+///
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonWebKey {
+    /// The specific cryptographic algorithm used with the key.
+    pub alg: String,
+    /// The family of cryptographic algorithms used with the key.
+    pub kty: String,
+    /// How the key was meant to be used; sig represents the signature.
+    #[serde(rename = "use", alias = "key_use")]
+    pub key_use: String,
+    /// The x.509 certificate chain. The first entry in the array is the certificate to use for
+    /// token verification; the other certificates can be used to verify this first certificate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5c: Option<Vec<String>>,
+    /// The modulus for the RSA public key.
+    pub n: String,
+    /// The exponent for the RSA public key.
+    pub e: String,
+    /// The unique identifier for the key.
+    pub kid: String,
+    /// The thumbprint of the x.509 cert (SHA-1 thumbprint).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x5t: Option<String>,
+    /// The operations the key is intended to be used for, e.g. `verify`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    /// Any other field of the key, e.g. vendor specific fields like `cloud_instance_name`.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+    /// Issuers the key is bound to, see [`JsonWebKey::issuers`]; never read from or written to
+    /// the JWKS document.
+    #[serde(skip)]
+    pub(crate) bound_issuers: BTreeSet<String>,
+}
+
+impl JsonWebKey {
+    /// RSA signature key `kid` for algorithm `alg`, e.g. `RS256`, with the base64url-encoded
+    /// modulus `n` and exponent `e`. The other fields are empty and can be set afterwards.
+    pub fn new(kid: &str, alg: &str, n: &str, e: &str) -> JsonWebKey {
+        JsonWebKey {
+            alg: alg.to_string(),
+            kty: "RSA".to_string(),
+            key_use: "sig".to_string(),
+            x5c: None,
+            n: n.to_string(),
+            e: e.to_string(),
+            kid: kid.to_string(),
+            x5t: None,
+            key_ops: None,
+            extra: Default::default(),
+            bound_issuers: Default::default(),
+        }
+    }
+
+    /// Bind the key to `issuer` as well, e.g. before [`Auth0::add_key`](crate::Auth0::add_key).
+    pub fn with_issuer(mut self, issuer: &str) -> JsonWebKey {
+        self.bound_issuers.insert(issuer.to_string());
+        self
+    }
+
+    /// Whether the key may be used to verify token signatures: `use` must be `sig` and, when
+    /// present, `key_ops` must contain `verify`.
+    pub fn is_signature_key(&self) -> bool {
+        if self.key_use != "sig" {
+            return false
+        }
+        match &self.key_ops {
+            Some(ops) => ops.iter().any(|op| op == "verify"),
+            None => true,
+        }
+    }
+
+    /// Issuers the key is bound to: those of the
+    /// [`Auth0Builder::issuer_jwks_url`](crate::builder::Auth0Builder::issuer_jwks_url) sources
+    /// serving it, or set with [`JsonWebKey::with_issuer`]. A bound key only verifies tokens whose
+    /// `iss` is one of them; an `issuer` member of the JWKS document itself binds nothing, see
+    /// [`JsonWebKey::extra_field`].
+    pub fn issuers(&self) -> &BTreeSet<String> {
+        &self.bound_issuers
+    }
+
+    /// Value of a field not covered by the other struct fields, e.g. `issuer` or
+    /// `cloud_instance_name`, useful for custom key selection.
+    pub fn extra_field(&self, name: &str) -> Option<&Value> {
+        self.extra.get(name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Jwks {
+    pub keys: Vec<JsonWebKey>
+}
+
+impl Jwks {
+    /// Parse a JSON web key set (JWKS) str.
+    pub fn parse(jwks_str: &str) -> Result<Jwks, Auth0Error> {
+        match serde_json::from_str(jwks_str) {
+            Ok(k) => Ok(k),
+            Err(_) => Err(new_error(ErrorKind::InvalidJwksStr)),
+        }
+    }
+
+    /// Parse a JWKS embedded in the binary, panicking with a precise message if it is invalid or
+    /// contains no keys. `source` names the embedded document in the panic message.
+    ///
+    /// Usually called through the [`include_jwks!`] macro.
+    pub fn from_embedded(jwks_str: &str, source: &str) -> Jwks {
+        let jwks: Jwks = match serde_json::from_str(jwks_str) {
+            Ok(k) => k,
+            Err(e) => panic!("invalid embedded JWKS `{}`: {}", source, e),
+        };
+        if jwks.keys.is_empty() {
+            panic!("invalid embedded JWKS `{}`: no keys", source);
+        }
+        for key in &jwks.keys {
+            if Algorithm::from_str(key.alg.as_str()).is_err() {
+                panic!("invalid embedded JWKS `{}`: key `{}` has unsupported alg `{}`", source, key.kid, key.alg);
+            }
+        }
+        jwks
+    }
+}
+
+/// Embed a JWKS file in the binary and parse it into a [`Jwks`], panicking at first use with a
+/// precise message if the document is invalid.
+///
+/// The path is resolved like [`include_str!`], relative to the current source file. Intended for
+/// air-gapped deployments shipping pinned keys.
+///
+/// Example:
+/// ```ignore
+/// use auth0_rs::{include_jwks, Auth0};
+/// let auth0 = Auth0::from_jwks(include_jwks!("keys.json"));
+/// ```
+#[macro_export]
+macro_rules! include_jwks {
+    ($path:expr) => {
+        $crate::Jwks::from_embedded(include_str!($path), $path)
+    };
+}
@@ -0,0 +1,200 @@
+//! Verification keys retrieved from a KMS (AWS KMS, GCP Cloud KMS) instead of a JWKS document.
+//!
+//! The crate does not talk to the KMS itself: implement [`KmsClient`] on top of the SDK your
+//! application already uses (`GetPublicKey` on AWS, `getPublicKey` on GCP) and register a
+//! [`KmsKeyProvider`] with [`Auth0Builder::key_provider`](crate::builder::Auth0Builder::key_provider).
+//!
+//! Example:
+//! ```ignore
+//! use auth0_rs::builder::Auth0Builder;
+//! use auth0_rs::kms::{KmsKeyProvider, KmsPublicKey};
+//!
+//! let client = |key_id: &str| {
+//!     let output = aws_kms.get_public_key().key_id(key_id).send_blocking()?;
+//!     Ok(KmsPublicKey { der: output.public_key.into_inner(), algorithm: "RSASSA_PKCS1_V1_5_SHA_256".to_string() })
+//! };
+//! let auth0 = Auth0Builder::new()
+//!     .key_provider(KmsKeyProvider::new(client).key("alias/token-signing", "signing-2024"))
+//!     .build()?;
+//! ```
+
+use std::sync::Arc;
+use simple_asn1::{from_der, ASN1Block, BigInt, BigUint, OID};
+use crate::{Algorithm, JsonWebKey};
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::source::KeyProvider;
+
+/// Public key of an asymmetric KMS key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KmsPublicKey {
+    /// DER encoded `SubjectPublicKeyInfo`; use [`pem_to_der`] on PEM encoded keys (GCP).
+    pub der: Vec<u8>,
+    /// Signing algorithm of the key, either a KMS name (`RSASSA_PKCS1_V1_5_SHA_256`,
+    /// `RSA_SIGN_PSS_2048_SHA256`, ...) or a JWA name (`RS256`, ...).
+    pub algorithm: String,
+}
+
+/// Client of the KMS public key API.
+///
+/// Closures `Fn(&str) -> Result<KmsPublicKey, Auth0Error>` taking the KMS key ID implement it
+/// too.
+pub trait KmsClient: Send + Sync {
+    /// Retrieve the public key of the KMS key `key_id`.
+    fn get_public_key(&self, key_id: &str) -> Result<KmsPublicKey, Auth0Error>;
+}
+
+impl<F> KmsClient for F
+    where F: Fn(&str) -> Result<KmsPublicKey, Auth0Error> + Send + Sync
+{
+    fn get_public_key(&self, key_id: &str) -> Result<KmsPublicKey, Auth0Error> {
+        self(key_id)
+    }
+}
+
+/// [`KeyProvider`] exposing KMS keys as [`JsonWebKey`]s.
+#[derive(Clone)]
+pub struct KmsKeyProvider {
+    client: Arc<dyn KmsClient>,
+    keys: Vec<(String, String)>,
+}
+
+impl std::fmt::Debug for KmsKeyProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KmsKeyProvider").field("keys", &self.keys).finish_non_exhaustive()
+    }
+}
+
+impl KmsKeyProvider {
+    /// Provider without keys, retrieving them with `client`.
+    pub fn new<C: KmsClient + 'static>(client: C) -> KmsKeyProvider {
+        KmsKeyProvider { client: Arc::new(client), keys: vec![] }
+    }
+
+    /// Expose the KMS key `key_id` (ID, ARN or alias) under the key ID `kid`, the value the
+    /// issuer puts in the `kid` header of its tokens.
+    pub fn key(mut self, key_id: &str, kid: &str) -> KmsKeyProvider {
+        self.keys.push((key_id.to_string(), kid.to_string()));
+        self
+    }
+}
+
+impl KeyProvider for KmsKeyProvider {
+    fn keys(&self) -> Result<Vec<JsonWebKey>, Auth0Error> {
+        let mut keys = Vec::with_capacity(self.keys.len());
+        for (key_id, kid) in &self.keys {
+            let public_key = self.client.get_public_key(key_id)?;
+            keys.push(kms_public_key_to_jwk(&public_key, kid)?);
+        }
+        Ok(keys)
+    }
+}
+
+/// Convert a KMS public key into a [`JsonWebKey`] with key ID `kid`.
+pub fn kms_public_key_to_jwk(public_key: &KmsPublicKey, kid: &str) -> Result<JsonWebKey, Auth0Error> {
+    let algorithm = match kms_algorithm(&public_key.algorithm) {
+        Some(algorithm) => algorithm,
+        None => return Err(invalid(format!("unsupported signing algorithm `{}`", public_key.algorithm))),
+    };
+    let (n, e) = rsa_components(&public_key.der)?;
+    Ok(JsonWebKey::new(kid, &format!("{:?}", algorithm), &n, &e))
+}
+
+/// JWA algorithm of a KMS signing algorithm name.
+fn kms_algorithm(name: &str) -> Option<Algorithm> {
+    let algorithm = match name {
+        "RS256" | "RSASSA_PKCS1_V1_5_SHA_256" => Algorithm::RS256,
+        "RS384" | "RSASSA_PKCS1_V1_5_SHA_384" => Algorithm::RS384,
+        "RS512" | "RSASSA_PKCS1_V1_5_SHA_512" => Algorithm::RS512,
+        "PS256" | "RSASSA_PSS_SHA_256" => Algorithm::PS256,
+        "PS384" | "RSASSA_PSS_SHA_384" => Algorithm::PS384,
+        "PS512" | "RSASSA_PSS_SHA_512" => Algorithm::PS512,
+        // GCP names carry the key size, e.g. RSA_SIGN_PKCS1_2048_SHA256
+        gcp if gcp.starts_with("RSA_SIGN_PKCS1_") && gcp.ends_with("_SHA256") => Algorithm::RS256,
+        gcp if gcp.starts_with("RSA_SIGN_PKCS1_") && gcp.ends_with("_SHA512") => Algorithm::RS512,
+        gcp if gcp.starts_with("RSA_SIGN_PSS_") && gcp.ends_with("_SHA256") => Algorithm::PS256,
+        gcp if gcp.starts_with("RSA_SIGN_PSS_") && gcp.ends_with("_SHA512") => Algorithm::PS512,
+        _ => return None,
+    };
+    Some(algorithm)
+}
+
+/// Decode a PEM document, e.g. a `-----BEGIN PUBLIC KEY-----` block, into DER.
+pub fn pem_to_der(pem: &str) -> Result<Vec<u8>, Auth0Error> {
+    let body: String = pem.lines()
+        .filter(|line| !line.starts_with("-----"))
+        .map(|line| line.trim())
+        .collect();
+    base64::decode(&body).map_err(|_| invalid("invalid PEM encoding".to_string()))
+}
+
+/// Base64url encoded modulus and exponent of a DER `SubjectPublicKeyInfo` RSA key.
+fn rsa_components(der: &[u8]) -> Result<(String, String), Auth0Error> {
+    let malformed = || invalid("malformed SubjectPublicKeyInfo".to_string());
+    let blocks = from_der(der).map_err(|_| malformed())?;
+    let (algorithm, public_key) = match blocks.first() {
+        Some(ASN1Block::Sequence(_, spki)) => match (spki.first(), spki.get(1)) {
+            (Some(ASN1Block::Sequence(_, algorithm)), Some(ASN1Block::BitString(_, _, public_key))) => (algorithm, public_key),
+            _ => return Err(malformed()),
+        },
+        _ => return Err(malformed()),
+    };
+    match algorithm.first() {
+        Some(ASN1Block::ObjectIdentifier(_, oid)) if *oid == rsa_encryption() => {}
+        _ => return Err(invalid("not an RSA key".to_string())),
+    }
+    let blocks = from_der(public_key).map_err(|_| malformed())?;
+    match blocks.first() {
+        Some(ASN1Block::Sequence(_, rsa)) => match (rsa.first(), rsa.get(1)) {
+            (Some(ASN1Block::Integer(_, n)), Some(ASN1Block::Integer(_, e))) => Ok((encode_integer(n), encode_integer(e))),
+            _ => Err(malformed()),
+        },
+        _ => Err(malformed()),
+    }
+}
+
+fn rsa_encryption() -> OID {
+    OID::new([1u32, 2, 840, 113549, 1, 1, 1].iter().map(|c| BigUint::from(*c)).collect())
+}
+
+fn encode_integer(integer: &BigInt) -> String {
+    let (_, bytes) = integer.to_bytes_be();
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn invalid(reason: String) -> Auth0Error {
+    new_error(ErrorKind::InvalidPublicKey(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::Auth0Builder;
+    use crate::test_utils::{TestKeyPair, short_lived_claims};
+    use crate::testing::TEST_KEY_N;
+    use super::*;
+
+    fn client(key_id: &str) -> Result<KmsPublicKey, Auth0Error> {
+        match key_id {
+            "alias/signing" => Ok(KmsPublicKey {
+                der: pem_to_der(include_str!("../testdata/rsa_1_pub.pem"))?,
+                algorithm: "RSASSA_PKCS1_V1_5_SHA_256".to_string(),
+            }),
+            _ => Err(new_error(ErrorKind::NoMatchKey)),
+        }
+    }
+
+    #[test]
+    fn test_kms_key_provider() {
+        let provider = KmsKeyProvider::new(client).key("alias/signing", "kms-1");
+        let keys = provider.keys().unwrap();
+        assert_eq!(keys[0].n, TEST_KEY_N);
+        assert_eq!(keys[0].e, "AQAB");
+        assert_eq!(keys[0].alg, "RS256");
+
+        let auth0 = Auth0Builder::new().key_provider(provider).build().unwrap();
+        let token = TestKeyPair::new("kms-1", Algorithm::RS256).sign(&short_lived_claims(60));
+        assert!(auth0.validate_token(&token).is_ok());
+
+        let unsupported = KmsPublicKey { der: vec![], algorithm: "ECDSA_SHA_256".to_string() };
+        assert!(matches!(kms_public_key_to_jwk(&unsupported, "x").unwrap_err().kind(), ErrorKind::InvalidPublicKey(_)));
+    }
+}
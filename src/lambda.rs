@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::{Auth0, Claims};
+use crate::bearer::parse_authorization;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// API Gateway custom authorizer event payload.
+///
+/// Covers REST API `TOKEN` and `REQUEST` authorizers as well as HTTP API (payload version 2.0)
+/// authorizers; fields not sent by a given payload type are `None`.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizerEvent {
+    /// `TOKEN` or `REQUEST`.
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    /// Payload version, `2.0` for HTTP APIs.
+    pub version: Option<String>,
+    /// Header value of `TOKEN` authorizers, e.g. `Bearer eyJ...`.
+    pub authorization_token: Option<String>,
+    /// ARN of the invoked method (REST APIs).
+    pub method_arn: Option<String>,
+    /// ARN of the invoked route (HTTP APIs).
+    pub route_arn: Option<String>,
+    /// Identity sources of HTTP API authorizers.
+    pub identity_source: Option<Vec<String>>,
+    /// Request headers of `REQUEST` authorizers.
+    pub headers: Option<HashMap<String, String>>,
+}
+
+impl AuthorizerEvent {
+    /// Bearer token of the request, looked up in `authorizationToken`, `identitySource`, then the
+    /// `Authorization` header.
+    pub fn bearer_token(&self) -> Option<&str> {
+        let header = self.headers.as_ref().and_then(|headers| {
+            headers.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+                .map(|(_, value)| value)
+        });
+        let identity = self.identity_source.as_ref().and_then(|sources| sources.first());
+        let value = self.authorization_token.as_ref().or(identity).or(header)?;
+        parse_authorization(value)
+    }
+}
+
+/// IAM policy response expected from REST API custom authorizers.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IamPolicyResponse {
+    /// Principal of the request, the token's `sub` claim.
+    pub principal_id: String,
+    /// Policy allowing or denying the invocation.
+    pub policy_document: PolicyDocument,
+    /// Values passed to the backend integration as `$context.authorizer.*`.
+    pub context: HashMap<String, Value>,
+}
+
+/// IAM policy document of an [`IamPolicyResponse`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyDocument {
+    /// Always `2012-10-17`.
+    pub version: String,
+    /// Policy statements.
+    pub statement: Vec<PolicyStatement>,
+}
+
+/// Single statement of a [`PolicyDocument`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyStatement {
+    /// Always `execute-api:Invoke`.
+    pub action: String,
+    /// `Allow` or `Deny`.
+    pub effect: String,
+    /// The method or route ARN the statement applies to.
+    pub resource: String,
+}
+
+/// Simple response expected from HTTP API authorizers with simple responses enabled.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimpleAuthorizerResponse {
+    /// Whether the request is authorized.
+    pub is_authorized: bool,
+    /// Values passed to the backend integration as `$context.authorizer.*`.
+    pub context: HashMap<String, Value>,
+}
+
+/// Validate the event's bearer token and build an `Allow` IAM policy for the invoked method.
+///
+/// On error, the Lambda handler should fail with the message `Unauthorized` so that API Gateway
+/// answers with `401`; use [`deny_policy`] instead to answer with `403`.
+pub fn authorize_iam_policy(auth0: &Auth0, event: &AuthorizerEvent) -> Result<IamPolicyResponse, Auth0Error> {
+    let claims = validate_event(auth0, event)?;
+    let principal_id = claims.get("sub").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    Ok(IamPolicyResponse {
+        principal_id,
+        policy_document: policy_document("Allow", event),
+        context: authorizer_context(&claims),
+    })
+}
+
+/// Build a `Deny` IAM policy for the invoked method.
+pub fn deny_policy(event: &AuthorizerEvent) -> IamPolicyResponse {
+    IamPolicyResponse {
+        principal_id: "unauthorized".to_string(),
+        policy_document: policy_document("Deny", event),
+        context: HashMap::new(),
+    }
+}
+
+/// Validate the event's bearer token and build an HTTP API simple response.
+pub fn authorize_simple(auth0: &Auth0, event: &AuthorizerEvent) -> SimpleAuthorizerResponse {
+    match validate_event(auth0, event) {
+        Ok(claims) => SimpleAuthorizerResponse { is_authorized: true, context: authorizer_context(&claims) },
+        Err(_) => SimpleAuthorizerResponse { is_authorized: false, context: HashMap::new() },
+    }
+}
+
+fn validate_event(auth0: &Auth0, event: &AuthorizerEvent) -> Result<Claims, Auth0Error> {
+    match event.bearer_token() {
+        Some(token) => auth0.validate_token(token),
+        None => Err(new_error(ErrorKind::MissingBearerToken)),
+    }
+}
+
+fn policy_document(effect: &str, event: &AuthorizerEvent) -> PolicyDocument {
+    let resource = event.method_arn.as_ref().or(event.route_arn.as_ref()).cloned().unwrap_or_default();
+    PolicyDocument {
+        version: "2012-10-17".to_string(),
+        statement: vec![PolicyStatement {
+            action: "execute-api:Invoke".to_string(),
+            effect: effect.to_string(),
+            resource,
+        }],
+    }
+}
+
+/// Authorizer context only supports string, number and boolean values, so other claims are dropped.
+fn authorizer_context(claims: &Claims) -> HashMap<String, Value> {
+    let mut context = HashMap::new();
+    if let Some(object) = claims.as_object() {
+        for (name, value) in object {
+            if value.is_string() || value.is_number() || value.is_boolean() {
+                context.insert(name.clone(), value.clone());
+            }
+        }
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::Algorithm;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_authorizers() {
+        let auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let event: AuthorizerEvent = serde_json::from_value(json!({
+            "type": "TOKEN",
+            "authorizationToken": format!("Bearer {}", token),
+            "methodArn": "arn:aws:execute-api:us-east-1:123456789012:api/prod/GET/orders"
+        })).unwrap();
+
+        let policy = serde_json::to_value(authorize_iam_policy(&auth0, &event).unwrap()).unwrap();
+        assert_eq!(policy["principalId"], "first-client");
+        assert_eq!(policy["policyDocument"]["Statement"][0]["Effect"], "Allow");
+
+        let event: AuthorizerEvent = serde_json::from_value(json!({
+            "version": "2.0",
+            "type": "REQUEST",
+            "routeArn": "arn:aws:execute-api:us-east-1:123456789012:api/$default/GET/orders",
+            "identitySource": ["Bearer invalid"]
+        })).unwrap();
+        assert!(!authorize_simple(&auth0, &event).is_authorized);
+
+        for value in ["Bearer ", "Bearer    ", "Basic abc", "Bearer"] {
+            let event: AuthorizerEvent = serde_json::from_value(json!({"type": "TOKEN", "authorizationToken": value})).unwrap();
+            assert_eq!(event.bearer_token(), None, "{:?}", value);
+        }
+        let event: AuthorizerEvent = serde_json::from_value(json!({"type": "REQUEST", "headers": {"Authorization": "bearer  abc "}})).unwrap();
+        assert_eq!(event.bearer_token(), Some("abc"));
+    }
+}
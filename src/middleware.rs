@@ -0,0 +1,229 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use http::{header, HeaderMap, HeaderValue, Request, Response, StatusCode};
+use pin_project_lite::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+use crate::{Auth0, Claims};
+use crate::bearer::parse_authorization;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Claims of a validated token, inserted into the request extensions by [`ValidateAuthorization`].
+#[derive(Debug, Clone)]
+pub struct ValidatedClaims(pub Claims);
+
+type FailureHandler<ResBody> = Arc<dyn Fn(&Auth0Error) -> Response<ResBody> + Send + Sync>;
+
+/// [`Layer`] validating the bearer token of every request before it reaches the wrapped service.
+///
+/// Requires the `tower` feature. The token is read like
+/// [`Auth0::validate_request`](crate::Auth0::validate_request) does, from the `Authorization`
+/// header or the configured token cookie. On success, the claims are available to the inner service as a
+/// [`ValidatedClaims`] request extension; on failure the request is answered with the configured
+/// failure response: by default `401 Unauthorized` with an empty body and a `WWW-Authenticate`
+/// challenge naming the [error code](crate::error::ErrorKind::code), e.g.
+/// `Bearer error="invalid_token", error_description="invalid_issuer"`.
+///
+/// Example:
+/// ```ignore
+/// use std::sync::Arc;
+/// use auth0_rs::middleware::ValidateAuthorizationLayer;
+/// let layer = ValidateAuthorizationLayer::new(Arc::new(auth0));
+/// let service = tower::ServiceBuilder::new().layer(layer).service(my_service);
+/// ```
+pub struct ValidateAuthorizationLayer<ResBody> {
+    auth0: Arc<Auth0>,
+    on_failure: FailureHandler<ResBody>,
+}
+
+impl<ResBody: Default + 'static> ValidateAuthorizationLayer<ResBody> {
+    /// Create a layer answering failed validations with `401 Unauthorized`, an empty body and a
+    /// `WWW-Authenticate` challenge.
+    pub fn new(auth0: Arc<Auth0>) -> Self {
+        ValidateAuthorizationLayer::with_failure_response(auth0, unauthorized_response)
+    }
+}
+
+impl<ResBody: 'static> ValidateAuthorizationLayer<ResBody> {
+    /// Create a layer building the failure response with a custom function.
+    pub fn with_failure_response<F>(auth0: Arc<Auth0>, on_failure: F) -> Self
+        where F: Fn(&Auth0Error) -> Response<ResBody> + Send + Sync + 'static
+    {
+        ValidateAuthorizationLayer { auth0, on_failure: Arc::new(on_failure) }
+    }
+}
+
+impl<ResBody> Clone for ValidateAuthorizationLayer<ResBody> {
+    fn clone(&self) -> Self {
+        ValidateAuthorizationLayer { auth0: self.auth0.clone(), on_failure: self.on_failure.clone() }
+    }
+}
+
+impl<S, ResBody> Layer<S> for ValidateAuthorizationLayer<ResBody> {
+    type Service = ValidateAuthorization<S, ResBody>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ValidateAuthorization {
+            inner,
+            auth0: self.auth0.clone(),
+            on_failure: self.on_failure.clone(),
+        }
+    }
+}
+
+/// Middleware created by [`ValidateAuthorizationLayer`].
+pub struct ValidateAuthorization<S, ResBody> {
+    inner: S,
+    auth0: Arc<Auth0>,
+    on_failure: FailureHandler<ResBody>,
+}
+
+impl<S: Clone, ResBody> Clone for ValidateAuthorization<S, ResBody> {
+    fn clone(&self) -> Self {
+        ValidateAuthorization {
+            inner: self.inner.clone(),
+            auth0: self.auth0.clone(),
+            on_failure: self.on_failure.clone(),
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ValidateAuthorization<S, ResBody>
+    where S: Service<Request<ReqBody>, Response = Response<ResBody>>
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, ResBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let result = self.auth0.validate_request(req.headers());
+        match result {
+            Ok(claims) => {
+                req.extensions_mut().insert(ValidatedClaims(claims));
+                ResponseFuture { kind: Kind::Inner { future: self.inner.call(req) } }
+            }
+            Err(e) => ResponseFuture { kind: Kind::Rejected { response: Some((self.on_failure)(&e)) } },
+        }
+    }
+}
+
+pin_project! {
+    /// Response future of [`ValidateAuthorization`].
+    pub struct ResponseFuture<F, ResBody> {
+        #[pin]
+        kind: Kind<F, ResBody>,
+    }
+}
+
+pin_project! {
+    #[project = KindProj]
+    enum Kind<F, ResBody> {
+        Inner { #[pin] future: F },
+        Rejected { response: Option<Response<ResBody>> },
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F, ResBody>
+    where F: Future<Output = Result<Response<ResBody>, E>>
+{
+    type Output = Result<Response<ResBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Inner { future } => future.poll(cx),
+            KindProj::Rejected { response } => {
+                Poll::Ready(Ok(response.take().expect("future polled after completion")))
+            }
+        }
+    }
+}
+
+/// Extract the bearer token from the `Authorization` header.
+pub fn bearer_token(headers: &HeaderMap) -> Result<&str, Auth0Error> {
+    headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_authorization)
+        .ok_or_else(|| new_error(ErrorKind::MissingBearerToken))
+}
+
+/// Default failure response: `401 Unauthorized` with a `WWW-Authenticate: Bearer` challenge.
+///
+/// The description is the error code rather than the message, which may quote the configured
+/// issuer or audience and values of the token.
+fn unauthorized_response<ResBody: Default>(error: &Auth0Error) -> Response<ResBody> {
+    let challenge = format!(r#"Bearer error="invalid_token", error_description="{}""#, error.kind().code());
+    let mut response = Response::new(ResBody::default());
+    *response.status_mut() = StatusCode::UNAUTHORIZED;
+    if let Ok(value) = HeaderValue::from_str(&challenge) {
+        response.headers_mut().insert(header::WWW_AUTHENTICATE, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use std::task::Waker;
+    use crate::Algorithm;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = std::future::Ready<Result<Response<String>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            let sub = req.extensions().get::<ValidatedClaims>().unwrap().0["sub"].to_string();
+            std::future::ready(Ok(Response::new(sub)))
+        }
+    }
+
+    fn call(service: &mut ValidateAuthorization<Echo, String>, authorization: Option<&str>) -> Response<String> {
+        let mut req = Request::new(());
+        if let Some(value) = authorization {
+            req.headers_mut().insert(header::AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        }
+        let mut future = Box::pin(service.call(req));
+        match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(res) => res.unwrap(),
+            Poll::Pending => panic!("future should be ready"),
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_layer() {
+        let auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let mut service = ValidateAuthorizationLayer::new(Arc::new(auth0)).layer(Echo);
+
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let response = call(&mut service, Some(&format!("Bearer {}", token)));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), "\"first-client\"");
+
+        let response = call(&mut service, None);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+
+        let options = crate::ValidationOptions { issuer: Some("https://tenant.auth0.com/".to_string()), ..Default::default() };
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let mut service = ValidateAuthorizationLayer::new(Arc::new(auth0)).layer(Echo);
+        let mut claims = test_claims();
+        claims["iss"] = serde_json::json!(r#"https://evil/" realm="x"#);
+        let response = call(&mut service, Some(&format!("Bearer {}", sign_token(Algorithm::RS256, &claims))));
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], r#"Bearer error="invalid_token", error_description="invalid_issuer""#);
+    }
+}
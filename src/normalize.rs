@@ -0,0 +1,190 @@
+//! Normalization of nonstandard claim encodings before typed deserialization.
+//!
+//! Issuers disagree on how some claims are encoded: scopes as a space-delimited string or as an
+//! array, booleans as `true` or `"true"`, numbers as strings. [`ClaimNormalizers`] rewrite each
+//! claim into one encoding, so one claims struct deserializes the tokens of every issuer with
+//! [`Auth0::validate_token_as`](crate::Auth0::validate_token_as).
+
+use std::fmt;
+use std::sync::Arc;
+use serde_json::Value;
+use crate::Claims;
+use crate::error::Auth0Error;
+use crate::pipeline::ClaimsStage;
+
+/// Encoding a claim is normalized into, see [`ClaimNormalizers::claim`].
+///
+/// Values already in the target encoding, and values that cannot be converted, are left as is.
+#[derive(Clone)]
+pub enum Normalization {
+    /// Array of strings: a space-delimited string is split, any other single value is wrapped in
+    /// an array.
+    List,
+    /// Space-delimited string: an array of strings is joined.
+    SpaceDelimited,
+    /// Boolean: `"true"` and `"false"` (in any case), `"1"`, `"0"`, `1` and `0` are converted.
+    Boolean,
+    /// Number: numeric strings are parsed.
+    Number,
+    /// Custom conversion of the claim value.
+    Custom(Arc<dyn Fn(&Value) -> Value + Send + Sync>),
+}
+
+impl Normalization {
+    /// Custom conversion of the claim value.
+    pub fn custom<F>(convert: F) -> Normalization
+        where F: Fn(&Value) -> Value + Send + Sync + 'static
+    {
+        Normalization::Custom(Arc::new(convert))
+    }
+
+    /// Normalized `value`.
+    pub fn apply(&self, value: &Value) -> Value {
+        match (self, value) {
+            (Normalization::List, Value::Array(_)) => value.clone(),
+            (Normalization::List, Value::String(list)) => list.split_whitespace().map(Value::from).collect(),
+            (Normalization::List, _) => Value::Array(vec![value.clone()]),
+            (Normalization::SpaceDelimited, Value::Array(items)) => match items.iter().map(Value::as_str).collect::<Option<Vec<&str>>>() {
+                Some(items) => Value::from(items.join(" ")),
+                None => value.clone(),
+            },
+            (Normalization::Boolean, Value::String(flag)) => match flag.to_ascii_lowercase().as_str() {
+                "true" | "1" => Value::Bool(true),
+                "false" | "0" => Value::Bool(false),
+                _ => value.clone(),
+            },
+            (Normalization::Boolean, Value::Number(flag)) => match flag.as_u64() {
+                Some(1) => Value::Bool(true),
+                Some(0) => Value::Bool(false),
+                _ => value.clone(),
+            },
+            (Normalization::Number, Value::String(number)) => serde_json::from_str::<serde_json::Number>(number.trim())
+                .map(Value::Number)
+                .unwrap_or_else(|_| value.clone()),
+            (Normalization::Custom(convert), _) => convert(value),
+            _ => value.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Normalization::List => f.write_str("List"),
+            Normalization::SpaceDelimited => f.write_str("SpaceDelimited"),
+            Normalization::Boolean => f.write_str("Boolean"),
+            Normalization::Number => f.write_str("Number"),
+            Normalization::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Per-claim [`Normalization`]s, applied by [`Auth0::validate_token_as`](crate::Auth0::validate_token_as)
+/// once set with [`Auth0::set_claim_normalizers`](crate::Auth0::set_claim_normalizers).
+///
+/// They are also a [`ClaimsStage`], to normalize the claims of every validation in a
+/// [`ClaimsPipeline`](crate::pipeline::ClaimsPipeline).
+///
+/// Example:
+/// ```
+/// use auth0_rs::normalize::{ClaimNormalizers, Normalization};
+/// use serde_json::json;
+/// let normalizers = ClaimNormalizers::new()
+///     .claim("scope", Normalization::List)
+///     .claim("email_verified", Normalization::Boolean);
+/// let mut claims = json!({"scope": "read:orders write:orders", "email_verified": "true"});
+/// normalizers.normalize(&mut claims);
+/// assert_eq!(claims, json!({"scope": ["read:orders", "write:orders"], "email_verified": true}));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClaimNormalizers {
+    rules: Vec<(String, Normalization)>,
+}
+
+impl ClaimNormalizers {
+    /// Create normalizers leaving every claim as is.
+    pub fn new() -> Self {
+        ClaimNormalizers::default()
+    }
+
+    /// Normalize claim `name` with `normalization`, after the normalizations added before.
+    pub fn claim(mut self, name: &str, normalization: Normalization) -> Self {
+        self.rules.push((name.to_string(), normalization));
+        self
+    }
+
+    /// Normalize `claims` in place; absent claims stay absent.
+    pub fn normalize(&self, claims: &mut Claims) {
+        if let Some(object) = claims.as_object_mut() {
+            for (name, normalization) in &self.rules {
+                if let Some(value) = object.get_mut(name) {
+                    *value = normalization.apply(value);
+                }
+            }
+        }
+    }
+}
+
+impl ClaimsStage for ClaimNormalizers {
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        self.normalize(claims);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::json;
+    use crate::{Algorithm, Auth0};
+    use crate::error::ErrorKind;
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        let cases = [
+            (Normalization::List, json!("a b"), json!(["a", "b"])),
+            (Normalization::List, json!(["a"]), json!(["a"])),
+            (Normalization::List, json!(7), json!([7])),
+            (Normalization::SpaceDelimited, json!(["a", "b"]), json!("a b")),
+            (Normalization::SpaceDelimited, json!(["a", 1]), json!(["a", 1])),
+            (Normalization::Boolean, json!("FALSE"), json!(false)),
+            (Normalization::Boolean, json!(1), json!(true)),
+            (Normalization::Boolean, json!("yes"), json!("yes")),
+            (Normalization::Number, json!(" 42 "), json!(42)),
+            (Normalization::Number, json!("4x"), json!("4x")),
+            (Normalization::custom(|value: &Value| json!(value.to_string())), json!(3), json!("3")),
+        ];
+        for (normalization, value, expected) in cases {
+            assert_eq!(normalization.apply(&value), expected, "{:?} of {}", normalization, value);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_validate_token_as() {
+        #[derive(Deserialize, Debug)]
+        struct OrderClaims {
+            sub: String,
+            scope: Vec<String>,
+            email_verified: bool,
+        }
+
+        let mut auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        auth0.set_claim_normalizers(ClaimNormalizers::new().claim("scope", Normalization::List).claim("email_verified", Normalization::Boolean));
+        let mut space_delimited = test_claims();
+        space_delimited["scope"] = json!("read:orders write:orders");
+        space_delimited["email_verified"] = json!("true");
+        let mut array = test_claims();
+        array["scope"] = json!(["read:orders"]);
+        array["email_verified"] = json!(true);
+
+        for claims in [space_delimited, array] {
+            let order_claims: OrderClaims = auth0.validate_token_as(&sign_token(Algorithm::RS256, &claims)).unwrap();
+            assert_eq!((order_claims.sub.as_str(), order_claims.scope[0].as_str(), order_claims.email_verified), ("first-client", "read:orders", true));
+        }
+        let err = auth0.validate_token_as::<OrderClaims>(&sign_token(Algorithm::RS256, &test_claims())).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ClaimsDeserialization(_)));
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use jsonwebtoken::Algorithm;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::assertion::ClaimsAssertion;
+
+/// Options controlling how tokens are validated by [`Auth0`](crate::Auth0).
+///
+/// Example:
+/// ```
+/// use auth0_rs::{Algorithm, ValidationOptions};
+/// let options = ValidationOptions {
+///     algorithms: vec![Algorithm::RS256, Algorithm::PS256],
+///     ..Default::default()
+/// };
+/// ```
+///
+/// Options serialize into the settings of a [`ValidationPolicy`](crate::policy::ValidationPolicy);
+/// missing fields deserialize to their defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ValidationOptions {
+    /// Allow-list of accepted signing algorithms.
+    ///
+    /// A token must always be signed with the algorithm declared by the `alg` field of its
+    /// matching key; the allow-list further restricts which of those are accepted. When empty
+    /// (the default), every RSA algorithm is allowed.
+    pub algorithms: Vec<Algorithm>,
+    /// Only accept keys meant for signature verification: `use` must be `sig` and, when present,
+    /// `key_ops` must contain `verify`.
+    ///
+    /// Defaults to `false`.
+    pub strict_key_use: bool,
+    /// Expected audiences; the token's `aud` must contain at least one of them.
+    ///
+    /// Defaults to `None`, meaning the audience is not checked.
+    pub audience: Option<Vec<String>>,
+    /// Map from request route prefix (e.g. `/orders`) to the expected audience of the API served
+    /// under it, used by [`Auth0::validate_token_for_route`](crate::Auth0::validate_token_for_route).
+    pub route_audiences: HashMap<String, String>,
+    /// Expected issuer, e.g. `https://YOUR_TENANT.auth0.com/`.
+    ///
+    /// Defaults to `None`, meaning the issuer is not checked.
+    pub issuer: Option<String>,
+    /// Issuers accepted besides [`issuer`](ValidationOptions::issuer), e.g. the tenant domain
+    /// while migrating to a custom domain. Tokens of any of them are accepted, and when several
+    /// issuers are accepted every validation emits a
+    /// [`ValidationWarning::IssuerMatched`](crate::ValidationWarning::IssuerMatched) naming the
+    /// one that matched, to track the progress of the migration.
+    ///
+    /// Defaults to none.
+    pub issuers: Vec<String>,
+    /// How the expected and observed issuers are normalized before comparison.
+    pub issuer_normalization: IssuerNormalization,
+    /// Leeway in seconds applied to the `exp` and `nbf` checks, to account for clock skew: a
+    /// token is accepted up to `leeway` seconds past its `exp` and before its `nbf`.
+    ///
+    /// Defaults to `0`.
+    pub leeway: u64,
+    /// Accepted values of the token header `typ`, e.g. `at+jwt` for RFC 9068 access tokens;
+    /// the header must then carry one of them. Values are compared case-insensitively, ignoring
+    /// an `application/` prefix (RFC 7515 section 4.1.9).
+    ///
+    /// Defaults to none, meaning `typ` is not checked.
+    pub token_types: Vec<String>,
+    /// Claims the token must carry, e.g. `iat` or `sub`, whatever their value.
+    pub required_claims: Vec<String>,
+    /// Scopes the token must all carry, in its space-delimited `scope` claim or in its
+    /// `permissions` array (Auth0 RBAC).
+    pub required_scopes: Vec<String>,
+    /// Map from audience to the scopes a token for that audience must all carry, for tokens
+    /// serving several APIs: a token whose `aud` contains `A` must carry every scope listed
+    /// under `A`, on top of [`required_scopes`](Self::required_scopes).
+    ///
+    /// Audiences absent from the map require no extra scope; see [`audience`](Self::audience)
+    /// to restrict the accepted audiences.
+    pub audience_scopes: HashMap<String, Vec<String>>,
+    /// What to do when the x5c certificate of the key matching a token is expired.
+    ///
+    /// Defaults to [`CertificateExpiryPolicy::Ignore`].
+    pub certificate_expiry: CertificateExpiryPolicy,
+    /// Grace period during which a token past its `exp` (and the leeway) is still accepted,
+    /// e.g. during failovers. It only extends `exp`: unlike [`leeway`](Self::leeway), it does
+    /// not relax the `nbf` check.
+    ///
+    /// Every token accepted this way emits
+    /// [`ValidationWarning::ExpiredWithinGrace`](crate::warning::ValidationWarning::ExpiredWithinGrace).
+    /// Defaults to zero, i.e. no grace period.
+    #[serde(with = "crate::policy::duration_secs")]
+    pub expired_grace: Duration,
+    /// Upper bound of the [`CachingHints::cacheable_for`](crate::cache::CachingHints::cacheable_for)
+    /// returned by [`Auth0::validate_token_with_hints`](crate::Auth0::validate_token_with_hints),
+    /// e.g. to bound how long a revoked token is still served from caches.
+    ///
+    /// Defaults to zero, meaning results may be cached until the token expires.
+    #[serde(with = "crate::policy::duration_secs")]
+    pub max_cacheable_for: Duration,
+    /// Reject tokens without a session ID (`sid`) claim, for APIs revoking tokens per session.
+    ///
+    /// Defaults to `false`.
+    pub require_session_id: bool,
+    /// Minimum strength of the authentication the token was issued after, read from its `amr`
+    /// and `acr` claims, e.g. to require multi-factor authentication.
+    ///
+    /// Defaults to accepting any authentication.
+    pub authentication_strength: AuthenticationStrength,
+    /// Size limits checked before any base64 or JSON decoding.
+    pub limits: TokenLimits,
+    /// Reject tokens with a segment that is not canonical unpadded base64url (padding, characters
+    /// outside the alphabet, non-zero trailing bits), before any signature verification.
+    ///
+    /// Defaults to `false`, accepting whatever the base64 decoder tolerates.
+    pub strict_base64: bool,
+    /// Critical header extensions (RFC 7515 `crit`) the application understands and processes
+    /// itself after a successful validation.
+    ///
+    /// Tokens whose `crit` lists any other extension are rejected with
+    /// [`ErrorKind::UnsupportedCriticalExtension`](crate::error::ErrorKind::UnsupportedCriticalExtension),
+    /// and tokens with a malformed `crit` with
+    /// [`ErrorKind::InvalidToken`](crate::error::ErrorKind::InvalidToken). Defaults to none.
+    pub critical_extensions: Vec<String>,
+    /// Maximum number of signed JWTs nested inside a token (RFC 7519 section 5.2, header
+    /// `cty: JWT`), for federation setups wrapping the token of an upstream issuer.
+    ///
+    /// The signature of each enclosing token is verified against the loaded keys before its
+    /// payload, the inner token, is validated in turn; the claims of the innermost token are
+    /// returned. Tokens nesting deeper are rejected with
+    /// [`ErrorKind::NestingTooDeep`](crate::error::ErrorKind::NestingTooDeep). Defaults to `0`,
+    /// rejecting nested tokens.
+    pub max_nesting_depth: usize,
+    /// Assertions the claims of every token must satisfy, e.g.
+    /// `scope contains "read:users" && email_verified`, see the
+    /// [`assertion`](crate::assertion) module.
+    ///
+    /// Tokens failing one are rejected with
+    /// [`ErrorKind::AssertionFailed`](crate::error::ErrorKind::AssertionFailed). In policy
+    /// documents, assertions are written as strings. Defaults to none.
+    pub assertions: Vec<ClaimsAssertion>,
+    /// Claims added to the returned claims of tokens lacking them, e.g. `roles: []` for tokens
+    /// issued before the claim existed. Present claims are never overwritten.
+    ///
+    /// Defaults are applied after validation, before the claims schema and pipeline.
+    pub default_claims: Map<String, Value>,
+    /// What to do when the loaded key set contains no keys.
+    ///
+    /// Defaults to [`EmptyKeySetPolicy::AwaitRefresh`].
+    pub empty_key_set: EmptyKeySetPolicy,
+    /// How long keys stay usable once [`Auth0::refresh_keys`](crate::Auth0::refresh_keys) keeps
+    /// failing, e.g. during a JWKS outage, see [`Auth0::key_health`](crate::Auth0::key_health).
+    ///
+    /// Defaults to using the last loaded keys indefinitely.
+    pub stale_keys: StaleKeyPolicy,
+    /// Name of a cookie carrying the token of requests without a bearer token, e.g. for browser
+    /// sessions, read by `Auth0::validate_request` with the `http` feature.
+    ///
+    /// Defaults to `None`, meaning only the `Authorization` header is read.
+    pub token_cookie: Option<String>,
+}
+
+/// Size limits of tokens, see [`ValidationOptions::limits`].
+///
+/// Tokens exceeding them are rejected with
+/// [`ErrorKind::TokenTooLarge`](crate::error::ErrorKind::TokenTooLarge) before any decoding work,
+/// protecting services from oversized crafted tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct TokenLimits {
+    /// Maximum size of the whole token, in bytes. Defaults to 16 KiB.
+    pub max_token_bytes: usize,
+    /// Maximum size of the base64url encoded header segment, in bytes. Defaults to 2 KiB.
+    pub max_header_bytes: usize,
+    /// Maximum size of the base64url encoded payload segment, in bytes. Defaults to 16 KiB.
+    pub max_payload_bytes: usize,
+}
+
+impl Default for TokenLimits {
+    fn default() -> Self {
+        TokenLimits {
+            max_token_bytes: 16 * 1024,
+            max_header_bytes: 2 * 1024,
+            max_payload_bytes: 16 * 1024,
+        }
+    }
+}
+
+/// Handling of keys whose x5c certificate is expired, see [`ValidationOptions::certificate_expiry`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateExpiryPolicy {
+    /// Do not look at certificates.
+    #[default]
+    Ignore,
+    /// Accept the key but emit [`ValidationWarning::CertificateExpired`](crate::warning::ValidationWarning::CertificateExpired).
+    Warn,
+    /// Reject tokens with [`ErrorKind::CertificateExpired`](crate::error::ErrorKind::CertificateExpired).
+    Refuse,
+}
+
+/// Handling of an empty key set, see [`ValidationOptions::empty_key_set`].
+///
+/// With any policy, an empty key set rejects every token with
+/// [`ErrorKind::NoMatchKey`](crate::error::ErrorKind::NoMatchKey).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyKeySetPolicy {
+    /// Accept an empty key set silently, e.g. when keys are loaded by a later
+    /// [`Auth0::refresh_keys`](crate::Auth0::refresh_keys).
+    #[default]
+    AwaitRefresh,
+    /// Accept an empty key set, but emit
+    /// [`ValidationWarning::EmptyKeySet`](crate::warning::ValidationWarning::EmptyKeySet) for every
+    /// token rejected because of it.
+    Warn,
+    /// Fail with [`ErrorKind::EmptyKeySet`](crate::error::ErrorKind::EmptyKeySet) when building an
+    /// instance or updating its keys with an empty key set, so misconfigurations are caught at
+    /// startup. Infallible constructors such as [`Auth0::from_jwks`](crate::Auth0::from_jwks) do
+    /// not check it.
+    Refuse,
+}
+
+/// Handling of keys that could not be refreshed, see [`ValidationOptions::stale_keys`].
+///
+/// Once a refresh fails, the last loaded keys are stale: they keep validating tokens, trading
+/// security (a revoked key stays trusted) for availability, until they are `max_staleness` old.
+/// Past that, tokens are rejected with
+/// [`ErrorKind::StaleKeys`](crate::error::ErrorKind::StaleKeys), failing closed, except on the
+/// `fail_open_routes`. The next successful refresh makes the keys fresh again.
+///
+/// Example:
+/// ```
+/// use std::time::Duration;
+/// use auth0_rs::options::StaleKeyPolicy;
+/// let policy = StaleKeyPolicy {
+///     max_staleness: Some(Duration::from_secs(6 * 3600)),
+///     fail_open_routes: vec!["/public/".to_string()],
+/// };
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct StaleKeyPolicy {
+    /// Age, since the last successful load, past which stale keys are no longer used; `None`
+    /// (the default) uses them indefinitely. Written in seconds in policy documents.
+    #[serde(with = "crate::policy::option_duration_secs")]
+    pub max_staleness: Option<Duration>,
+    /// Route prefixes, e.g. `/public/`, on which tokens are still validated with keys past
+    /// `max_staleness`. Routes are taken from the
+    /// [`ValidationContext::route`](crate::context::ValidationContext::route) of
+    /// [`Auth0::validate_token_with_context`](crate::Auth0::validate_token_with_context).
+    pub fail_open_routes: Vec<String>,
+}
+
+impl StaleKeyPolicy {
+    /// Whether `route` is one of the [`fail_open_routes`](Self::fail_open_routes).
+    pub fn fails_open(&self, route: &str) -> bool {
+        self.fail_open_routes.iter().any(|prefix| route.starts_with(prefix.as_str()))
+    }
+}
+
+/// Authentication requirements, see [`ValidationOptions::authentication_strength`].
+///
+/// Tokens failing them are rejected with
+/// [`ErrorKind::InsufficientAuthentication`](crate::error::ErrorKind::InsufficientAuthentication),
+/// telling the client to have the user step up, e.g. log in again with MFA. Auth0 lists `mfa` in
+/// `amr` once the user completed an MFA challenge.
+///
+/// Example:
+/// ```
+/// use auth0_rs::options::AuthenticationStrength;
+/// let strength = AuthenticationStrength {
+///     required_methods: vec!["mfa".to_string()],
+///     ..Default::default()
+/// };
+/// let acr = AuthenticationStrength {
+///     acr_levels: vec!["urn:example:loa:1".to_string(), "urn:example:loa:2".to_string()],
+///     minimum_acr: Some("urn:example:loa:2".to_string()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AuthenticationStrength {
+    /// Methods the `amr` claim must all list, e.g. `mfa`.
+    pub required_methods: Vec<String>,
+    /// Known `acr` values, from the weakest to the strongest.
+    pub acr_levels: Vec<String>,
+    /// Weakest accepted `acr`: the token's `acr` must be this value or a stronger one of
+    /// `acr_levels`. Defaults to `None`, accepting any `acr`, including none.
+    pub minimum_acr: Option<String>,
+}
+
+impl AuthenticationStrength {
+    /// Whether any authentication is accepted.
+    pub fn is_empty(&self) -> bool {
+        self.required_methods.is_empty() && self.minimum_acr.is_none()
+    }
+
+    /// Whether `acr` is at least [`minimum_acr`](Self::minimum_acr).
+    pub fn accepts_acr(&self, acr: Option<&str>) -> bool {
+        let minimum = match &self.minimum_acr {
+            Some(minimum) => minimum,
+            None => return true,
+        };
+        let level = |acr: &str| self.acr_levels.iter().position(|level| level == acr);
+        match acr {
+            Some(acr) if acr == minimum => true,
+            Some(acr) => matches!((level(acr), level(minimum)), (Some(actual), Some(minimum)) if actual >= minimum),
+            None => false,
+        }
+    }
+}
+
+/// Per-call adjustments of the [`ValidationOptions`], see
+/// [`Auth0::validate_token_with`](crate::Auth0::validate_token_with).
+///
+/// Example:
+/// ```
+/// use auth0_rs::ValidationOverrides;
+/// let overrides = ValidationOverrides {
+///     audience: Some(vec!["https://billing.example.com".to_string()]),
+///     required_scopes: vec!["read:invoices".to_string()],
+///     leeway: Some(30),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOverrides {
+    /// Replaces [`ValidationOptions::audience`].
+    pub audience: Option<Vec<String>>,
+    /// Replaces [`ValidationOptions::issuer`].
+    pub issuer: Option<String>,
+    /// Scopes required on top of [`ValidationOptions::required_scopes`].
+    pub required_scopes: Vec<String>,
+    /// Replaces [`ValidationOptions::leeway`].
+    pub leeway: Option<u64>,
+    /// Replaces [`ValidationOptions::authentication_strength`], e.g. to require MFA on the
+    /// sensitive routes only.
+    pub authentication_strength: Option<AuthenticationStrength>,
+}
+
+impl ValidationOverrides {
+    /// Options resulting from applying these overrides to `options`.
+    pub fn apply(&self, options: &ValidationOptions) -> ValidationOptions {
+        let mut options = options.clone();
+        if let Some(audience) = &self.audience {
+            options.audience = Some(audience.clone());
+        }
+        if let Some(issuer) = &self.issuer {
+            options.issuer = Some(issuer.clone());
+        }
+        for scope in &self.required_scopes {
+            if !options.required_scopes.contains(scope) {
+                options.required_scopes.push(scope.clone());
+            }
+        }
+        if let Some(leeway) = self.leeway {
+            options.leeway = leeway;
+        }
+        if let Some(strength) = &self.authentication_strength {
+            options.authentication_strength = strength.clone();
+        }
+        options
+    }
+}
+
+/// Normalization applied to both the expected and the observed issuer before comparing them.
+///
+/// The default tolerates a missing or extra trailing slash and compares scheme and host
+/// case-insensitively; use [`IssuerNormalization::strict`] for exact matching.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct IssuerNormalization {
+    /// Ignore trailing slashes, Auth0 issuers always end with one.
+    pub trailing_slash: bool,
+    /// Compare the scheme and host case-insensitively.
+    pub case_insensitive_host: bool,
+}
+
+impl IssuerNormalization {
+    /// Exact issuer matching.
+    pub fn strict() -> Self {
+        IssuerNormalization { trailing_slash: false, case_insensitive_host: false }
+    }
+}
+
+impl Default for IssuerNormalization {
+    fn default() -> Self {
+        IssuerNormalization { trailing_slash: true, case_insensitive_host: true }
+    }
+}
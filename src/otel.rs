@@ -0,0 +1,133 @@
+//! OpenTelemetry instrumentation of token validation, enabled by the `otel` feature.
+//!
+//! Every validation runs in an `auth0.validate_token` span of the globally installed tracer
+//! provider, child of the current context, carrying these attributes:
+//!
+//! - `auth0.kid` and `auth0.alg`: key ID and algorithm from the token header, when decodable;
+//! - `auth0.outcome`: `valid` or `invalid`;
+//! - `auth0.issuer`: the `iss` claim of a valid token;
+//! - `auth0.error_code`: the [`code`](crate::error::ErrorKind::code) of the failure.
+//! - `auth0.request_id` and `auth0.route`: from the [`ValidationContext`](crate::context::ValidationContext)
+//!   of validations started by [`Auth0::validate_token_with_context`](crate::Auth0::validate_token_with_context).
+//!
+//! Failures are also recorded as `exception` span events and set the span status to error.
+
+use jsonwebtoken::decode_header;
+use opentelemetry::{global, Context, KeyValue};
+use opentelemetry::trace::{Status, TraceContextExt, Tracer};
+use crate::Claims;
+use crate::error::Auth0Error;
+
+/// Name of the validation span.
+pub const SPAN_NAME: &str = "auth0.validate_token";
+
+/// Run `validate` in a validation span of the global tracer.
+pub(crate) fn instrument<F>(token: &str, validate: F) -> Result<Claims, Auth0Error>
+    where F: FnOnce() -> Result<Claims, Auth0Error>
+{
+    instrument_with(&global::tracer("auth0_rs"), token, validate)
+}
+
+fn instrument_with<T, F>(tracer: &T, token: &str, validate: F) -> Result<Claims, Auth0Error>
+    where T: Tracer, T::Span: Send + Sync + 'static, F: FnOnce() -> Result<Claims, Auth0Error>
+{
+    let cx = Context::current_with_span(tracer.start(SPAN_NAME));
+    let _guard = cx.clone().attach();
+    let span = cx.span();
+    if let Ok(header) = decode_header(token) {
+        if let Some(kid) = header.kid {
+            span.set_attribute(KeyValue::new("auth0.kid", kid));
+        }
+        span.set_attribute(KeyValue::new("auth0.alg", format!("{:?}", header.alg)));
+    }
+    crate::context::with_current(|context| {
+        if let Some(request_id) = context.and_then(|context| context.request_id.clone()) {
+            span.set_attribute(KeyValue::new("auth0.request_id", request_id));
+        }
+        if let Some(route) = context.and_then(|context| context.route.clone()) {
+            span.set_attribute(KeyValue::new("auth0.route", route));
+        }
+    });
+
+    let result = validate();
+    match &result {
+        Ok(claims) => {
+            span.set_attribute(KeyValue::new("auth0.outcome", "valid"));
+            if let Some(issuer) = claims.get("iss").and_then(|v| v.as_str()) {
+                span.set_attribute(KeyValue::new("auth0.issuer", issuer.to_string()));
+            }
+        }
+        Err(error) => {
+            span.set_attribute(KeyValue::new("auth0.outcome", "invalid"));
+            span.set_attribute(KeyValue::new("auth0.error_code", error.kind().code()));
+            span.record_error(error);
+            span.set_status(Status::error(error.to_string()));
+        }
+    }
+    span.end();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+    use opentelemetry::trace::{Span, SpanBuilder, SpanContext};
+    use crate::{Algorithm, Auth0};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[derive(Default, Clone)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct RecordingSpan(Recorder, SpanContext);
+
+    impl Span for RecordingSpan {
+        fn add_event_with_timestamp<T: Into<std::borrow::Cow<'static, str>>>(&mut self, name: T, _timestamp: SystemTime, _attributes: Vec<KeyValue>) {
+            self.0.0.lock().unwrap().push(format!("event {}", name.into()));
+        }
+        fn span_context(&self) -> &SpanContext {
+            &self.1
+        }
+        fn is_recording(&self) -> bool {
+            true
+        }
+        fn set_attribute(&mut self, attribute: KeyValue) {
+            self.0.0.lock().unwrap().push(format!("{}={}", attribute.key, attribute.value));
+        }
+        fn set_status(&mut self, status: Status) {
+            self.0.0.lock().unwrap().push(format!("status {:?}", status));
+        }
+        fn update_name<T: Into<std::borrow::Cow<'static, str>>>(&mut self, _new_name: T) {}
+        fn add_link(&mut self, _span_context: SpanContext, _attributes: Vec<KeyValue>) {}
+        fn end_with_timestamp(&mut self, _timestamp: SystemTime) {
+            self.0.0.lock().unwrap().push("end".to_string());
+        }
+    }
+
+    impl Tracer for Recorder {
+        type Span = RecordingSpan;
+
+        fn build_with_context(&self, builder: SpanBuilder, _parent_cx: &Context) -> RecordingSpan {
+            self.0.lock().unwrap().push(format!("start {}", builder.name));
+            RecordingSpan(self.clone(), SpanContext::empty_context())
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_validation_span() {
+        let auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let recorder = Recorder::default();
+        assert!(instrument_with(&recorder, &token, || auth0.validate_token(&token)).is_ok());
+        let records = recorder.0.lock().unwrap().clone();
+        assert_eq!(records, vec!["start auth0.validate_token", "auth0.kid=test", "auth0.alg=RS256", "auth0.outcome=valid", "end"]);
+
+        let recorder = Recorder::default();
+        assert!(instrument_with(&recorder, "garbage", || auth0.validate_token("garbage")).is_err());
+        let records = recorder.0.lock().unwrap().clone();
+        assert!(records.contains(&"auth0.error_code=invalid_token".to_string()));
+        assert!(records.contains(&"event exception".to_string()));
+    }
+}
@@ -0,0 +1,207 @@
+//! Deterministic, panic-free parsers of untrusted input.
+//!
+//! These are the first steps of every validation, exposed so fuzzing harnesses (see the `fuzz`
+//! directory, run with `cargo fuzz run token`) and gateways can exercise them directly with
+//! arbitrary bytes. They never panic and fail with [`ErrorKind::InvalidJwksStr`] or
+//! [`ErrorKind::InvalidToken`].
+
+use std::fmt;
+use jsonwebtoken::Header;
+use serde_json::{Map, Value};
+use crate::Jwks;
+use crate::secret::SecretString;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Parse a JWKS document from raw bytes, which need not be valid UTF-8.
+pub fn parse_jwks_bytes(data: &[u8]) -> Result<Jwks, Auth0Error> {
+    serde_json::from_slice(data).map_err(|_| new_error(ErrorKind::InvalidJwksStr))
+}
+
+/// The three base64url segments of a compact JWS token, see [`split_token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenSegments<'a> {
+    /// Encoded header.
+    pub header: &'a str,
+    /// Encoded payload.
+    pub payload: &'a str,
+    /// Encoded signature.
+    pub signature: &'a str,
+    signing_input: &'a str,
+}
+
+impl<'a> TokenSegments<'a> {
+    /// The signed part of the token: `header.payload`.
+    pub fn signing_input(&self) -> &'a str {
+        self.signing_input
+    }
+
+    /// Decode the header as a JSON object.
+    pub fn decode_header(&self) -> Result<Map<String, Value>, Auth0Error> {
+        decode_segment(self.header).and_then(|header| serde_json::from_slice(&header).map_err(|_| invalid()))
+    }
+
+    /// Decode the payload as JSON, e.g. the claims.
+    pub fn decode_payload(&self) -> Result<Value, Auth0Error> {
+        decode_segment(self.payload).and_then(|payload| serde_json::from_slice(&payload).map_err(|_| invalid()))
+    }
+
+    /// Decode the signature bytes.
+    pub fn decode_signature(&self) -> Result<Vec<u8>, Auth0Error> {
+        decode_segment(self.signature)
+    }
+}
+
+/// Split a compact JWS token given as raw bytes into its three segments, without decoding them.
+///
+/// Fails if the token is not UTF-8 or does not have exactly three segments.
+pub fn split_token(token: &[u8]) -> Result<TokenSegments<'_>, Auth0Error> {
+    let token = std::str::from_utf8(token).map_err(|_| invalid())?;
+    let mut segments = token.split('.');
+    match (segments.next(), segments.next(), segments.next(), segments.next()) {
+        (Some(header), Some(payload), Some(signature), None) => {
+            let signing_input = &token[..header.len() + 1 + payload.len()];
+            Ok(TokenSegments { header, payload, signature, signing_input })
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// A compact JWS token decoded once, to be validated several times without being parsed again,
+/// e.g. by gateways authorizing a request in several stages against different audiences.
+///
+/// Parsing splits the segments and decodes the header, the payload and the signature bytes, but
+/// trusts nothing: validate the token with [`Auth0::validate_parsed`](crate::Auth0::validate_parsed)
+/// or [`Auth0::validate_parsed_with`](crate::Auth0::validate_parsed_with), which also apply
+/// [`ValidationOptions::limits`](crate::ValidationOptions::limits) and the header checks.
+///
+/// Example:
+/// ```ignore
+/// let token = ParsedToken::parse(token)?;
+/// let claims = auth0.validate_parsed_for_audience(&token, "https://orders.example.com")?;
+/// auth0.validate_parsed_with(&token, &ValidationOverrides { required_scopes: vec!["write:orders".to_string()], ..Default::default() })?;
+/// ```
+#[derive(Clone)]
+pub struct ParsedToken {
+    token: SecretString,
+    header: Header,
+    header_fields: Map<String, Value>,
+    payload: Vec<u8>,
+    claims: Option<Value>,
+    signature: Vec<u8>,
+}
+
+impl ParsedToken {
+    /// Parse a compact JWS token.
+    ///
+    /// Fails if the token does not have three base64url segments or its header is not a JWS
+    /// header. The payload need not be JSON, e.g. when it encloses a nested token.
+    pub fn parse(token: &str) -> Result<ParsedToken, Auth0Error> {
+        let segments = split_token(token.as_bytes())?;
+        let header_fields = segments.decode_header()?;
+        let header = serde_json::from_value(Value::Object(header_fields.clone())).map_err(|_| invalid())?;
+        let payload = decode_segment(segments.payload)?;
+        let claims = serde_json::from_slice(&payload).ok();
+        let signature = segments.decode_signature()?;
+        Ok(ParsedToken { token: SecretString::from(token), header, header_fields, payload, claims, signature })
+    }
+
+    /// The raw token.
+    pub fn as_str(&self) -> &str {
+        self.token.as_str()
+    }
+
+    /// The decoded header.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Every field of the header, including those [`Header`] does not know, e.g. `crit`.
+    pub fn header_fields(&self) -> &Map<String, Value> {
+        &self.header_fields
+    }
+
+    /// The decoded payload.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// The claims of the payload, not verified in any way, or `None` if the payload is not JSON.
+    pub fn unverified_claims(&self) -> Option<&Value> {
+        self.claims.as_ref()
+    }
+
+    /// The decoded signature bytes.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// The signed part of the token: `header.payload`, and its encoded signature.
+    pub(crate) fn signed_parts(&self) -> (&str, &str) {
+        self.as_str().rsplit_once('.').unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for ParsedToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParsedToken")
+            .field("header", &self.header)
+            .field("claims", &self.claims)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ParsedToken {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.payload);
+        zeroize::Zeroize::zeroize(&mut self.signature);
+    }
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, Auth0Error> {
+    base64::decode_config(segment, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())
+}
+
+fn invalid() -> Auth0Error {
+    new_error(ErrorKind::InvalidToken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hostile_input() {
+        let segments = split_token(b"eyJhbGciOiJSUzI1NiJ9.e30.c2ln").unwrap();
+        assert_eq!(segments.signing_input(), "eyJhbGciOiJSUzI1NiJ9.e30");
+        assert_eq!(segments.decode_header().unwrap()["alg"], "RS256");
+        assert_eq!(segments.decode_signature().unwrap(), b"sig");
+
+        for token in [&b"a.b"[..], b"a.b.c.d", b"\xff\xfe.e30.c2ln", b"", b".."] {
+            assert!(split_token(token).is_err() || split_token(token).unwrap().decode_header().is_err());
+        }
+        let huge_exp = format!("e30.{}.", base64::encode_config(r#"{"exp":1e400}"#, base64::URL_SAFE_NO_PAD));
+        assert!(split_token(huge_exp.as_bytes()).unwrap().decode_payload().is_err());
+        assert!(parse_jwks_bytes(b"\xff{\"keys\": []}").is_err());
+        assert!(parse_jwks_bytes(br#"{"keys": [{"kty": 1}]}"#).is_err());
+        assert!(parse_jwks_bytes(br#"{"keys": []}"#).unwrap().keys.is_empty());
+    }
+
+    #[test]
+    fn test_parsed_token() {
+        let token = crate::testing::sign_token(crate::Algorithm::RS256, &crate::testing::test_claims());
+        let parsed = ParsedToken::parse(&token).unwrap();
+        assert_eq!(parsed.as_str(), token);
+        assert_eq!((parsed.header().alg, parsed.header().kid.as_deref()), (crate::Algorithm::RS256, Some("test")));
+        assert_eq!(parsed.unverified_claims().unwrap()["sub"], "first-client");
+        assert_eq!(parsed.signed_parts().0, split_token(token.as_bytes()).unwrap().signing_input());
+        assert_eq!(parsed.signature().len(), 256);
+        assert!(!format!("{:?}", parsed).contains(&token));
+
+        let nested = format!("eyJhbGciOiJSUzI1NiIsImN0eSI6IkpXVCJ9.{}.c2ln", base64::encode_config("a.b.c", base64::URL_SAFE_NO_PAD));
+        let parsed = ParsedToken::parse(&nested).unwrap();
+        assert_eq!((parsed.payload(), parsed.unverified_claims()), (&b"a.b.c"[..], None));
+        assert!(ParsedToken::parse("e30.e30.c2ln").is_err());
+        assert!(ParsedToken::parse("eyJhbGciOiJSUzI1NiJ9.e30").is_err());
+    }
+}
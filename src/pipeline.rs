@@ -0,0 +1,200 @@
+use serde_json::{Map, Value};
+use crate::Claims;
+use crate::context::ValidationContext;
+use crate::error::Auth0Error;
+
+/// A single transformation applied to validated claims by a [`ClaimsPipeline`].
+///
+/// Closures `Fn(&mut Claims) -> Result<(), Auth0Error>` implement this trait too.
+pub trait ClaimsStage: Send + Sync {
+    /// Transform the claims in place. Returning an error rejects the token.
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error>;
+
+    /// Like [`ClaimsStage::apply`], with the context of the validation if it was started by
+    /// [`Auth0::validate_token_with_context`](crate::Auth0::validate_token_with_context), e.g.
+    /// to check claims against the request route.
+    fn apply_with_context(&self, claims: &mut Claims, _context: Option<&ValidationContext>) -> Result<(), Auth0Error> {
+        self.apply(claims)
+    }
+}
+
+impl<F> ClaimsStage for F
+    where F: Fn(&mut Claims) -> Result<(), Auth0Error> + Send + Sync
+{
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        self(claims)
+    }
+}
+
+/// Ordered list of [`ClaimsStage`]s applied to claims after successful validation.
+///
+/// Attach it with [`Auth0::set_claims_pipeline`](crate::Auth0::set_claims_pipeline), so every
+/// service sharing the pipeline gets the same claim shapes.
+///
+/// Example:
+/// ```
+/// use auth0_rs::pipeline::ClaimsPipeline;
+/// use serde_json::json;
+/// let pipeline = ClaimsPipeline::new()
+///     .strip_namespace("https://example.com/")
+///     .rename("permissions", "scopes")
+///     .redact(&["email"]);
+/// let mut claims = json!({"https://example.com/roles": ["admin"], "permissions": ["read"], "email": "a@b.c"});
+/// pipeline.apply(&mut claims).unwrap();
+/// assert_eq!(claims, json!({"roles": ["admin"], "scopes": ["read"]}));
+/// ```
+#[derive(Default)]
+pub struct ClaimsPipeline {
+    stages: Vec<Box<dyn ClaimsStage>>,
+}
+
+impl ClaimsPipeline {
+    /// Create an empty pipeline.
+    pub fn new() -> Self {
+        ClaimsPipeline::default()
+    }
+
+    /// Append a custom stage.
+    pub fn stage<S: ClaimsStage + 'static>(mut self, stage: S) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Remove `prefix` from claim names, e.g. `https://example.com/roles` becomes `roles`.
+    /// Existing claims with the stripped name are overwritten.
+    pub fn strip_namespace(self, prefix: &str) -> Self {
+        self.stage(StripNamespace(prefix.to_string()))
+    }
+
+    /// Rename claim `from` to `to`, if present.
+    pub fn rename(self, from: &str, to: &str) -> Self {
+        self.stage(Rename { from: from.to_string(), to: to.to_string() })
+    }
+
+    /// Insert claims returned by a lookup function, e.g. roles fetched from a database by `sub`.
+    pub fn enrich<F>(self, lookup: F) -> Self
+        where F: Fn(&Claims) -> Result<Map<String, Value>, Auth0Error> + Send + Sync + 'static
+    {
+        self.stage(Enrich(lookup))
+    }
+
+    /// Remove the given claims.
+    pub fn redact(self, names: &[&str]) -> Self {
+        self.stage(Redact(names.iter().map(|n| n.to_string()).collect()))
+    }
+
+    /// Number of stages.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Whether the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Apply every stage in order, stopping at the first error.
+    pub fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        self.apply_with_context(claims, None)
+    }
+
+    /// Apply every stage in order with the context of the validation, stopping at the first error.
+    pub fn apply_with_context(&self, claims: &mut Claims, context: Option<&ValidationContext>) -> Result<(), Auth0Error> {
+        for stage in &self.stages {
+            stage.apply_with_context(claims, context)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stage removing a namespace prefix from claim names.
+pub struct StripNamespace(pub String);
+
+impl ClaimsStage for StripNamespace {
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        if let Some(object) = claims.as_object_mut() {
+            let names: Vec<String> = object.keys().filter(|k| k.starts_with(self.0.as_str())).cloned().collect();
+            for name in names {
+                if let Some(value) = object.remove(&name) {
+                    object.insert(name[self.0.len()..].to_string(), value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stage renaming a claim.
+pub struct Rename {
+    /// Original claim name.
+    pub from: String,
+    /// New claim name.
+    pub to: String,
+}
+
+impl ClaimsStage for Rename {
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        if let Some(object) = claims.as_object_mut() {
+            if let Some(value) = object.remove(&self.from) {
+                object.insert(self.to.clone(), value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stage inserting claims returned by a lookup function.
+pub struct Enrich<F>(pub F);
+
+impl<F> ClaimsStage for Enrich<F>
+    where F: Fn(&Claims) -> Result<Map<String, Value>, Auth0Error> + Send + Sync
+{
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        let extra = (self.0)(claims)?;
+        if let Some(object) = claims.as_object_mut() {
+            object.extend(extra);
+        }
+        Ok(())
+    }
+}
+
+/// Stage removing claims.
+pub struct Redact(pub Vec<String>);
+
+impl ClaimsStage for Redact {
+    fn apply(&self, claims: &mut Claims) -> Result<(), Auth0Error> {
+        if let Some(object) = claims.as_object_mut() {
+            for name in &self.0 {
+                object.remove(name);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pipeline_order() {
+        let pipeline = ClaimsPipeline::new()
+            .enrich(|claims| {
+                let mut extra = Map::new();
+                if claims["sub"] == "user-1" {
+                    extra.insert("tenant".to_string(), json!("acme"));
+                }
+                Ok(extra)
+            })
+            .stage(|claims: &mut Claims| {
+                claims["checked"] = json!(true);
+                Ok(())
+            })
+            .redact(&["sub"]);
+
+        let mut claims = json!({"sub": "user-1"});
+        pipeline.apply(&mut claims).unwrap();
+        assert_eq!(claims, json!({"tenant": "acme", "checked": true}));
+    }
+}
@@ -0,0 +1,124 @@
+//! Versioned, serializable snapshots of the validation settings.
+
+use serde::{Deserialize, Serialize};
+use crate::ValidationOptions;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Version of the policy document format written by this release.
+pub const POLICY_VERSION: u32 = 1;
+
+/// Serializable snapshot of every [`ValidationOptions`] setting, tagged with the version of the
+/// document format.
+///
+/// Export the active policy with [`Auth0::policy`](crate::Auth0::policy) and install one with
+/// [`Auth0::set_policy`](crate::Auth0::set_policy), which returns the replaced policy, so
+/// operators can roll out a stricter policy from a file or a configuration service and revert
+/// to the previous one without restarting.
+///
+/// Example:
+/// ```
+/// use auth0_rs::policy::ValidationPolicy;
+/// let policy = ValidationPolicy::from_json(r#"{
+///     "version": 1,
+///     "issuer": "https://YOUR_TENANT.auth0.com/",
+///     "algorithms": ["RS256"],
+///     "expired_grace": 30
+/// }"#).unwrap();
+/// assert_eq!(policy.options.expired_grace.as_secs(), 30);
+/// assert_eq!(policy.options.leeway, 0);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ValidationPolicy {
+    /// Version of the document format, at most [`POLICY_VERSION`].
+    pub version: u32,
+    /// The settings. Settings missing from a document take their default value; durations are
+    /// written in seconds.
+    #[serde(flatten)]
+    pub options: ValidationOptions,
+}
+
+impl ValidationPolicy {
+    /// Snapshot of `options` in the current format version.
+    pub fn new(options: ValidationOptions) -> ValidationPolicy {
+        ValidationPolicy { version: POLICY_VERSION, options }
+    }
+
+    /// Parse a JSON policy document, failing with [`ErrorKind::InvalidPolicy`] if it is malformed
+    /// or written by a newer release.
+    pub fn from_json(policy: &str) -> Result<ValidationPolicy, Auth0Error> {
+        let policy: ValidationPolicy = serde_json::from_str(policy)
+            .map_err(|e| new_error(ErrorKind::InvalidPolicy(e.to_string())))?;
+        policy.check_version()?;
+        Ok(policy)
+    }
+
+    /// Serialize the policy as a pretty-printed JSON document.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("policies always serialize")
+    }
+
+    /// Fail with [`ErrorKind::InvalidPolicy`] if the format version is not supported.
+    pub(crate) fn check_version(&self) -> Result<(), Auth0Error> {
+        if self.version == 0 || self.version > POLICY_VERSION {
+            return Err(new_error(ErrorKind::InvalidPolicy(format!("unsupported version {}", self.version))))
+        }
+        Ok(())
+    }
+}
+
+/// (De)serialize a [`Duration`](std::time::Duration) as whole seconds.
+pub(crate) mod duration_secs {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
+
+/// (De)serialize an optional [`Duration`](std::time::Duration) as whole seconds or `null`.
+pub(crate) mod option_duration_secs {
+    use std::time::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&duration.as_secs()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|secs| secs.map(Duration::from_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Algorithm, Auth0};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_policy_roundtrip() {
+        let mut auth0 = Auth0::new(&test_jwks("RS256")).unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let exported = auth0.policy().to_json();
+
+        let strict = ValidationPolicy::from_json(r#"{"version": 1, "required_scopes": ["admin"]}"#).unwrap();
+        let previous = auth0.set_policy(strict).unwrap();
+        assert!(auth0.validate_token(&token).is_err());
+        auth0.set_policy(previous).unwrap();
+        assert!(auth0.validate_token(&token).is_ok());
+        assert_eq!(auth0.policy().to_json(), exported);
+
+        let future = ValidationPolicy::from_json(r#"{"version": 2}"#).unwrap_err();
+        assert!(matches!(future.kind(), ErrorKind::InvalidPolicy(_)));
+        assert!(ValidationPolicy::from_json(r#"{"version": 1, "leeway": "soon"}"#).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Ready-made validation settings for well-known kinds of Auth0 tokens.
+
+use jsonwebtoken::Algorithm;
+use crate::ValidationOptions;
+
+/// Validation settings for a well-known kind of token, selected with
+/// [`Auth0Builder::preset`](crate::builder::Auth0Builder::preset).
+///
+/// Example:
+/// ```
+/// use auth0_rs::preset::{management_scopes, ValidationPreset};
+/// let preset = ValidationPreset::ManagementApi { tenant: "acme.eu.auth0.com".to_string() };
+/// let mut options = preset.options();
+/// options.required_scopes.push(management_scopes::READ_USERS.to_string());
+/// assert_eq!(options.audience, Some(vec!["https://acme.eu.auth0.com/api/v2/".to_string()]));
+/// assert_eq!(options.issuer.as_deref(), Some("https://acme.eu.auth0.com/"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationPreset {
+    /// Access tokens for the Auth0 Management API of `tenant`, e.g. `acme.eu.auth0.com`.
+    ///
+    /// Management API tokens always use the canonical tenant domain, even on tenants with a
+    /// custom domain: the audience is `https://{tenant}/api/v2/` (trailing slash included) and
+    /// the issuer `https://{tenant}/`. They are signed with RS256 and carry their permissions in
+    /// the `scope` claim; require the scopes an operation needs with
+    /// [`ValidationOptions::required_scopes`] and the [`management_scopes`] constants. A scheme
+    /// or trailing slash in `tenant` is ignored.
+    ManagementApi {
+        /// Canonical domain of the tenant.
+        tenant: String,
+    },
+}
+
+impl ValidationPreset {
+    /// Validation options of the preset; the other options keep their defaults.
+    pub fn options(&self) -> ValidationOptions {
+        match self {
+            ValidationPreset::ManagementApi { .. } => ValidationOptions {
+                algorithms: vec![Algorithm::RS256],
+                audience: Some(vec![format!("{}api/v2/", self.base_url())]),
+                issuer: Some(self.base_url()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// URL of the JWKS document holding the keys signing the tokens of the preset.
+    pub fn jwks_url(&self) -> String {
+        format!("{}.well-known/jwks.json", self.base_url())
+    }
+
+    /// `https://{tenant}/`.
+    fn base_url(&self) -> String {
+        match self {
+            ValidationPreset::ManagementApi { tenant } => {
+                let tenant = tenant.trim_start_matches("https://").trim_start_matches("http://");
+                format!("https://{}/", tenant.trim_end_matches('/'))
+            }
+        }
+    }
+}
+
+/// Scopes of the Auth0 Management API commonly required from its access tokens.
+pub mod management_scopes {
+    /// Read users.
+    pub const READ_USERS: &str = "read:users";
+    /// Create users.
+    pub const CREATE_USERS: &str = "create:users";
+    /// Update users.
+    pub const UPDATE_USERS: &str = "update:users";
+    /// Delete users.
+    pub const DELETE_USERS: &str = "delete:users";
+    /// Read the `app_metadata` of users.
+    pub const READ_USERS_APP_METADATA: &str = "read:users_app_metadata";
+    /// Update the `app_metadata` of users.
+    pub const UPDATE_USERS_APP_METADATA: &str = "update:users_app_metadata";
+    /// Read roles.
+    pub const READ_ROLES: &str = "read:roles";
+    /// Create, update and delete roles and their assignments.
+    pub const UPDATE_ROLES: &str = "update:roles";
+    /// Read applications.
+    pub const READ_CLIENTS: &str = "read:clients";
+    /// Read the secrets of applications.
+    pub const READ_CLIENT_KEYS: &str = "read:client_keys";
+    /// Read organizations.
+    pub const READ_ORGANIZATIONS: &str = "read:organizations";
+    /// Read tenant logs.
+    pub const READ_LOGS: &str = "read:logs";
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{Algorithm, Auth0};
+    use crate::error::ErrorKind;
+    use crate::testing::{test_jwks, sign_token};
+    use super::*;
+
+    #[test]
+    fn test_management_api_preset() {
+        let preset = ValidationPreset::ManagementApi { tenant: "https://acme.auth0.com/".to_string() };
+        assert_eq!(preset.jwks_url(), "https://acme.auth0.com/.well-known/jwks.json");
+        let mut options = preset.options();
+        options.required_scopes.push(management_scopes::READ_USERS.to_string());
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+
+        let claims = |aud: &str, scope: &str| json!({
+            "iss": "https://acme.auth0.com/", "aud": aud, "scope": scope, "gty": "client-credentials", "exp": 32520059430u64,
+        });
+        assert!(auth0.validate_token(&sign_token(Algorithm::RS256, &claims("https://acme.auth0.com/api/v2/", "read:users update:users"))).is_ok());
+        let custom_domain = sign_token(Algorithm::RS256, &claims("https://login.acme.com/api/v2/", "read:users"));
+        assert!(matches!(auth0.validate_token(&custom_domain).unwrap_err().kind(), ErrorKind::InvalidAudience));
+        assert!(auth0.validate_token(&sign_token(Algorithm::RS256, &claims("https://acme.auth0.com/api/v2/", "read:logs"))).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Security hardening profiles bundling recommended validation settings.
+
+use jsonwebtoken::Algorithm;
+use crate::ValidationOptions;
+
+/// Bundle of hardening settings, so a safe configuration takes one line.
+///
+/// A profile only manages [`leeway`](ValidationOptions::leeway),
+/// [`algorithms`](ValidationOptions::algorithms), [`token_types`](ValidationOptions::token_types),
+/// [`required_claims`](ValidationOptions::required_claims),
+/// [`strict_base64`](ValidationOptions::strict_base64) and
+/// [`strict_key_use`](ValidationOptions::strict_key_use). Select it with
+/// [`Auth0Builder::security_profile`](crate::builder::Auth0Builder::security_profile), or start
+/// from [`SecurityProfile::options`] to override single options:
+///
+/// ```
+/// use auth0_rs::ValidationOptions;
+/// use auth0_rs::profile::SecurityProfile;
+/// let options = ValidationOptions {
+///     issuer: Some("https://YOUR_TENANT.auth0.com/".to_string()),
+///     leeway: 5,
+///     ..SecurityProfile::Strict.options()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityProfile {
+    /// For new deployments: no leeway, RS256 or PS256 only, header `typ` of `JWT` or `at+jwt`,
+    /// `exp`, `iat`, `iss`, `aud` and `sub` required, canonical base64url and signature keys
+    /// only.
+    Strict,
+    /// For most deployments: 30 seconds of leeway, any RSA algorithm, `exp` required, keys
+    /// restricted to signatures.
+    Balanced,
+    /// For old clients and tenants: 60 seconds of leeway and no other restriction beyond the
+    /// defaults.
+    Legacy,
+}
+
+impl SecurityProfile {
+    /// Default options hardened according to the profile.
+    pub fn options(self) -> ValidationOptions {
+        let mut options = ValidationOptions::default();
+        self.apply(&mut options);
+        options
+    }
+
+    /// Overwrite the options managed by the profile, keeping the others.
+    pub fn apply(self, options: &mut ValidationOptions) {
+        let strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+        match self {
+            SecurityProfile::Strict => {
+                options.leeway = 0;
+                options.algorithms = vec![Algorithm::RS256, Algorithm::PS256];
+                options.token_types = strings(&["JWT", "at+jwt"]);
+                options.required_claims = strings(&["exp", "iat", "iss", "aud", "sub"]);
+                options.strict_base64 = true;
+                options.strict_key_use = true;
+            }
+            SecurityProfile::Balanced => {
+                options.leeway = 30;
+                options.algorithms = vec![
+                    Algorithm::RS256, Algorithm::RS384, Algorithm::RS512,
+                    Algorithm::PS256, Algorithm::PS384, Algorithm::PS512,
+                ];
+                options.token_types = vec![];
+                options.required_claims = strings(&["exp"]);
+                options.strict_base64 = false;
+                options.strict_key_use = true;
+            }
+            SecurityProfile::Legacy => {
+                options.leeway = 60;
+                options.algorithms = vec![];
+                options.token_types = vec![];
+                options.required_claims = vec![];
+                options.strict_base64 = false;
+                options.strict_key_use = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::Auth0;
+    use crate::error::ErrorKind;
+    use crate::test_utils::{now, TestKeyPair};
+    use super::*;
+
+    #[test]
+    fn test_security_profiles() {
+        let key = TestKeyPair::new("test", Algorithm::RS256);
+        let jwks = key.jwks_string();
+        let strict = Auth0::with_options(&jwks, SecurityProfile::Strict.options()).unwrap();
+        let balanced = Auth0::with_options(&jwks, SecurityProfile::Balanced.options()).unwrap();
+        let legacy = Auth0::with_options(&jwks, SecurityProfile::Legacy.options()).unwrap();
+
+        let complete = json!({"iss": "https://tenant/", "aud": "api", "sub": "a", "iat": now(), "exp": now() + 60});
+        assert!(strict.validate_token(&key.sign(&complete)).is_ok());
+        let at_jwt = key.sign_with_raw_header(&json!({"alg": "RS256", "kid": "test", "typ": "application/AT+JWT"}), &complete);
+        assert!(strict.validate_token(&at_jwt).is_ok());
+        let untyped = key.sign_with_raw_header(&json!({"alg": "RS256", "kid": "test"}), &complete);
+        assert!(matches!(strict.validate_token(&untyped).unwrap_err().kind(), ErrorKind::InvalidTokenType(None)));
+
+        let minimal = json!({"exp": now() - 20});
+        assert!(strict.validate_token(&key.sign(&minimal)).is_err());
+        let no_iat = json!({"iss": "https://tenant/", "aud": "api", "sub": "a", "exp": now() + 60});
+        assert!(matches!(strict.validate_token(&key.sign(&no_iat)).unwrap_err().kind(), ErrorKind::MissingClaim(name) if name == "iat"));
+        assert!(balanced.validate_token(&key.sign(&minimal)).is_ok());
+        assert!(balanced.validate_token(&key.sign(&json!({"exp": now() - 40}))).is_err());
+        assert!(legacy.validate_token(&key.sign(&json!({"exp": now() - 50}))).is_ok());
+    }
+}
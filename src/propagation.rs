@@ -0,0 +1,168 @@
+//! Propagation of validated claims to upstream services as HTTP headers, for gateways.
+
+use serde_json::Value;
+use crate::Claims;
+use crate::validation::token_scopes;
+
+/// Source of a propagated header value.
+#[derive(Debug, Clone, PartialEq)]
+enum HeaderSource {
+    /// A claim, by name.
+    Claim(String),
+    /// Every scope of the token, from `scope` and `permissions`, space-separated.
+    Scopes,
+}
+
+/// Mapping of validated claims to HTTP headers.
+///
+/// Values are sanitized so no claim can inject headers: every byte outside printable ASCII, as
+/// well as `%`, is percent-encoded (so CR and LF never reach the header), and header names are
+/// restricted to RFC 9110 token characters, others being replaced with `-`. Strings are used as
+/// is, arrays of scalars are joined with `,`, other values are sent as compact JSON. Absent or
+/// `null` claims, and a token without scopes, produce no header.
+///
+/// A gateway must also drop these headers from incoming requests before adding them, so clients
+/// cannot forge them; [`ClaimHeaders::header_names`] lists them.
+///
+/// Example:
+/// ```
+/// use auth0_rs::propagation::ClaimHeaders;
+/// use serde_json::json;
+/// let mapping = ClaimHeaders::standard().map("https://example.com/tenant", "X-User-Tenant");
+/// let claims = json!({"sub": "auth0|1\r\nX-Admin: true", "scope": "read:a read:b", "https://example.com/tenant": "acme"});
+/// assert_eq!(mapping.headers(&claims), vec![
+///     ("X-User-Sub".to_string(), "auth0|1%0D%0AX-Admin: true".to_string()),
+///     ("X-User-Scopes".to_string(), "read:a read:b".to_string()),
+///     ("X-User-Tenant".to_string(), "acme".to_string()),
+/// ]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaimHeaders {
+    mappings: Vec<(HeaderSource, String)>,
+}
+
+impl ClaimHeaders {
+    /// Create a mapping without any header.
+    pub fn new() -> Self {
+        ClaimHeaders::default()
+    }
+
+    /// Create a mapping sending `sub` as `X-User-Sub` and the scopes as `X-User-Scopes`.
+    pub fn standard() -> Self {
+        ClaimHeaders::new().map("sub", "X-User-Sub").scopes("X-User-Scopes")
+    }
+
+    /// Send the `claim` claim as the `header` header.
+    pub fn map(mut self, claim: &str, header: &str) -> Self {
+        self.mappings.push((HeaderSource::Claim(claim.to_string()), sanitize_name(header)));
+        self
+    }
+
+    /// Send the scopes of the token (its `scope` claim and `permissions` array) as the `header`
+    /// header, space-separated.
+    pub fn scopes(mut self, header: &str) -> Self {
+        self.mappings.push((HeaderSource::Scopes, sanitize_name(header)));
+        self
+    }
+
+    /// Names of the headers this mapping may produce.
+    pub fn header_names(&self) -> Vec<&str> {
+        self.mappings.iter().map(|(_, header)| header.as_str()).collect()
+    }
+
+    /// Headers for `claims`, as `(name, value)` pairs in mapping order.
+    pub fn headers(&self, claims: &Claims) -> Vec<(String, String)> {
+        self.mappings.iter()
+            .filter_map(|(source, header)| {
+                let value = match source {
+                    HeaderSource::Claim(claim) => claim_value(claims.get(claim)?)?,
+                    HeaderSource::Scopes => match token_scopes(claims) {
+                        scopes if scopes.is_empty() => return None,
+                        scopes => scopes.join(" "),
+                    },
+                };
+                Some((header.clone(), sanitize_value(&value)))
+            })
+            .collect()
+    }
+
+    /// Insert the headers for `claims` into `headers`, replacing any value they already had.
+    ///
+    /// Requires the `tower` feature.
+    #[cfg(feature = "tower")]
+    pub fn insert_into(&self, claims: &Claims, headers: &mut http::HeaderMap) {
+        for name in self.header_names() {
+            if let Ok(name) = http::HeaderName::from_bytes(name.as_bytes()) {
+                headers.remove(name);
+            }
+        }
+        for (name, value) in self.headers(claims) {
+            if let (Ok(name), Ok(value)) = (http::HeaderName::from_bytes(name.as_bytes()), http::HeaderValue::from_str(&value)) {
+                headers.insert(name, value);
+            }
+        }
+    }
+}
+
+fn claim_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Array(items) if items.iter().all(|item| !item.is_array() && !item.is_object()) => {
+            Some(items.iter().filter_map(claim_value).collect::<Vec<String>>().join(","))
+        }
+        other => Some(other.to_string()),
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c) { c } else { '-' })
+        .collect();
+    if name.is_empty() { "-".to_string() } else { name }
+}
+
+fn sanitize_value(value: &str) -> String {
+    let mut sanitized = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'%' => sanitized.push_str("%25"),
+            0x20..=0x7E => sanitized.push(byte as char),
+            _ => sanitized.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    sanitized
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn test_claim_headers() {
+        let mapping = ClaimHeaders::new()
+            .map("roles", "X-User-Roles")
+            .map("org", "X-User-Org")
+            .map("name", "X-User\nName")
+            .map("missing", "X-Missing");
+        let claims = json!({"roles": ["admin", "ops"], "org": {"id": 1}, "name": "Zoë 100%"});
+        assert_eq!(mapping.headers(&claims), vec![
+            ("X-User-Roles".to_string(), "admin,ops".to_string()),
+            ("X-User-Org".to_string(), r#"{"id":1}"#.to_string()),
+            ("X-User-Name".to_string(), "Zo%C3%AB 100%25".to_string()),
+        ]);
+        assert_eq!(mapping.header_names(), vec!["X-User-Roles", "X-User-Org", "X-User-Name", "X-Missing"]);
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn test_insert_into() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-user-sub", http::HeaderValue::from_static("forged"));
+        headers.insert("x-user-scopes", http::HeaderValue::from_static("admin"));
+        ClaimHeaders::standard().insert_into(&json!({"sub": "auth0|1"}), &mut headers);
+        assert_eq!(headers["x-user-sub"], "auth0|1");
+        assert!(!headers.contains_key("x-user-scopes"));
+    }
+}
@@ -0,0 +1,72 @@
+//! Validation of the token of an HTTP request, enabled by the `http` feature.
+//!
+//! A lowest-common-denominator integration for frameworks built on the `http` crate that have no
+//! dedicated adapter: hand the request headers to [`Auth0::validate_request`].
+//!
+//! Example:
+//! ```ignore
+//! let claims = auth0.validate_request(request.headers())?;
+//! ```
+
+use http::{header, HeaderMap};
+use crate::{Auth0, Claims};
+use crate::bearer::parse_authorization;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+impl Auth0 {
+    /// Validate the token of a request like [`Auth0::validate_token`], reading it from the
+    /// `Authorization` bearer token or, without one, from the
+    /// [`ValidationOptions::token_cookie`](crate::ValidationOptions::token_cookie) cookie.
+    ///
+    /// Fails with [`ErrorKind::MissingBearerToken`] if the request carries neither.
+    pub fn validate_request(&self, headers: &HeaderMap) -> Result<Claims, Auth0Error> {
+        self.validate_token(request_token(headers, self.options().token_cookie.as_deref())?)
+    }
+}
+
+/// Token of a request: the `Authorization` bearer token, else the value of the `cookie` cookie.
+pub fn request_token<'h>(headers: &'h HeaderMap, cookie: Option<&str>) -> Result<&'h str, Auth0Error> {
+    let bearer = headers.get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_authorization);
+    bearer.or_else(|| cookie.and_then(|name| cookie_value(headers, name)))
+        .ok_or_else(|| new_error(ErrorKind::MissingBearerToken))
+}
+
+/// Value of the cookie `name`, from any of the `Cookie` headers (there may be several over HTTP/2).
+fn cookie_value<'h>(headers: &'h HeaderMap, name: &str) -> Option<&'h str> {
+    headers.get_all(header::COOKIE).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie, _)| *cookie == name)
+        .map(|(_, value)| value.trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+    use crate::{Algorithm, ValidationOptions};
+    use crate::testing::{test_jwks, sign_token, test_claims};
+    use super::*;
+
+    #[test]
+    fn test_validate_request() {
+        let options = ValidationOptions { token_cookie: Some("access_token".to_string()), ..Default::default() };
+        let auth0 = Auth0::with_options(&test_jwks("RS256"), options).unwrap();
+        let token = sign_token(Algorithm::RS256, &test_claims());
+
+        let mut headers = HeaderMap::new();
+        assert!(matches!(auth0.validate_request(&headers).unwrap_err().kind(), ErrorKind::MissingBearerToken));
+        headers.append(header::COOKIE, HeaderValue::from_static("theme=dark"));
+        headers.append(header::COOKIE, HeaderValue::from_str(&format!("session=1; access_token={}", token)).unwrap());
+        assert_eq!(auth0.validate_request(&headers).unwrap()["sub"], "first-client");
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer not-a-token"));
+        assert!(matches!(auth0.validate_request(&headers).unwrap_err().kind(), ErrorKind::InvalidToken));
+        assert_eq!(request_token(&headers, None).unwrap(), "not-a-token");
+        headers.remove(header::AUTHORIZATION);
+        assert!(request_token(&headers, Some("theme=")).is_err());
+    }
+}
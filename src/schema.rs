@@ -0,0 +1,75 @@
+use jsonschema::JSONSchema;
+use serde_json::Value;
+use crate::Claims;
+use crate::error::{new_error, ErrorKind, Auth0Error, SchemaViolation};
+
+/// JSON Schema document that validated claims must satisfy.
+///
+/// Requires the `schema` feature. Attach it with [`Auth0::set_claims_schema`](crate::Auth0::set_claims_schema).
+///
+/// Example:
+/// ```
+/// use auth0_rs::schema::ClaimsSchema;
+/// use serde_json::json;
+/// let schema = ClaimsSchema::new(&json!({
+///     "type": "object",
+///     "required": ["https://example.com/tenant"],
+///     "properties": {
+///         "https://example.com/tenant": {"type": "string"},
+///         "https://example.com/plan": {"enum": ["free", "enterprise"]}
+///     }
+/// })).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ClaimsSchema {
+    compiled: JSONSchema,
+}
+
+impl ClaimsSchema {
+    /// Compile a JSON Schema document.
+    pub fn new(schema: &Value) -> Result<ClaimsSchema, Auth0Error> {
+        match JSONSchema::compile(schema) {
+            Ok(compiled) => Ok(ClaimsSchema { compiled }),
+            Err(_) => Err(new_error(ErrorKind::InvalidClaimsSchema)),
+        }
+    }
+
+    /// Check claims against the schema, returning every violation found.
+    pub fn validate(&self, claims: &Claims) -> Result<(), Auth0Error> {
+        if let Err(errors) = self.compiled.validate(claims) {
+            let violations = errors
+                .map(|e| SchemaViolation {
+                    path: e.instance_path.to_string(),
+                    message: e.to_string(),
+                })
+                .collect();
+            return Err(new_error(ErrorKind::ClaimsSchemaViolation(violations)))
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_claims_schema() {
+        let schema = ClaimsSchema::new(&json!({
+            "type": "object",
+            "required": ["tenant"],
+            "properties": {"plan": {"enum": ["free", "enterprise"]}}
+        })).unwrap();
+
+        assert!(schema.validate(&json!({"tenant": "acme", "plan": "free"})).is_ok());
+        let err = schema.validate(&json!({"plan": "gold"})).unwrap_err();
+        match err.kind() {
+            ErrorKind::ClaimsSchemaViolation(violations) => {
+                assert_eq!(violations.len(), 2);
+                assert!(violations.iter().any(|v| v.path == "/plan"));
+            }
+            _ => panic!("unexpected error {:?}", err),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Buffers holding credentials.
+//!
+//! With the `zeroize` feature, these buffers are overwritten with zeros when dropped, so client
+//! secrets and tokens do not linger in freed memory. Key material held by `jsonwebtoken` and the
+//! embedded keys of the `test-utils` feature, which are public constants, are out of
+//! reach.
+
+use std::fmt;
+
+/// String holding a credential, e.g. a client secret or a token.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct SecretString(String);
+
+impl SecretString {
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        SecretString(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        SecretString(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(..)")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SecretString {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_string() {
+        let secret = SecretString::from("s3cr3t");
+        assert_eq!(secret.clone().as_str(), "s3cr3t");
+        assert_eq!(format!("{:?}", secret), "SecretString(..)");
+
+        #[cfg(feature = "zeroize")]
+        {
+            let mut secret = secret;
+            zeroize::Zeroize::zeroize(&mut secret);
+            assert_eq!(secret.as_str(), "");
+        }
+    }
+}
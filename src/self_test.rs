@@ -0,0 +1,82 @@
+use std::str::FromStr;
+use serde_json::json;
+use crate::{Algorithm, Auth0, Jwks, JsonWebKey, is_rsa_algorithm};
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::test_utils::{TestKeyPair, short_lived_claims};
+
+impl Auth0 {
+    /// Run a cryptographic self-test of every loaded key, intended for startup checks.
+    ///
+    /// Requires the `test-utils` feature. For each key, this checks the declared algorithm and the
+    /// RSA key size, then mints a short-lived token with an embedded test key pair declared under
+    /// the same `kid` and `alg`, and validates it through the full pipeline with the configured
+    /// options. The loaded key itself must reject that token.
+    ///
+    /// Claims schema, claims pipeline and signature cache are not exercised.
+    pub fn self_test(&self) -> Result<(), Auth0Error> {
+        for (kid, key) in &self.key_map {
+            let fail = |reason: String| new_error(ErrorKind::SelfTestFailed { kid: kid.clone(), reason });
+
+            let alg = match Algorithm::from_str(key.alg.as_str()) {
+                Ok(alg) if is_rsa_algorithm(alg) => alg,
+                _ => return Err(fail(format!("unsupported algorithm `{}`", key.alg))),
+            };
+            let modulus_bits = match base64::decode_config(&key.n, base64::URL_SAFE_NO_PAD) {
+                Ok(n) => n.iter().skip_while(|b| **b == 0).count() * 8,
+                Err(_) => return Err(fail("modulus is not valid base64url".to_string())),
+            };
+            if !(2048..=8192).contains(&modulus_bits) {
+                return Err(fail(format!("unsupported RSA key size of {} bits", modulus_bits)))
+            }
+            if base64::decode_config(&key.e, base64::URL_SAFE_NO_PAD).is_err() {
+                return Err(fail("exponent is not valid base64url".to_string()))
+            }
+
+            let test_key = TestKeyPair::new(kid, alg);
+            let probe_key = JsonWebKey {
+                key_use: key.key_use.clone(),
+                key_ops: key.key_ops.clone(),
+                ..test_key.jwk()
+            };
+            let probe = Auth0::from_jwks_with_options(Jwks { keys: vec![probe_key] }, self.options.clone());
+
+            let mut claims = short_lived_claims(60);
+            if let Some(issuer) = &self.options.issuer {
+                claims["iss"] = json!(issuer);
+            }
+            if let Some(audience) = self.options.audience.as_ref().and_then(|a| a.first()) {
+                claims["aud"] = json!(audience);
+            }
+            let token = test_key.sign(&claims);
+            if let Err(e) = probe.validate_token(&token) {
+                return Err(fail(format!("known-answer token rejected: {}", e)))
+            }
+            if self.verify_signature_with_key(&token, kid, key, alg).is_ok() {
+                return Err(fail("key is a public test key".to_string()))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationOptions;
+    use crate::testing::test_jwks;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_self_test() {
+        let keys = include_str!("../testdata/jwks.json").replace("\"test\"", "\"prod\"");
+        let options = ValidationOptions { issuer: Some("https://tenant.auth0.com/".to_string()), ..Default::default() };
+        let auth0 = Auth0::with_options(&keys, options).unwrap();
+        assert!(matches!(auth0.self_test().unwrap_err().kind(), ErrorKind::SelfTestFailed { reason, .. } if reason == "key is a public test key"));
+
+        let baseline = r#"{"keys":[{"kty":"RSA","n":"nzyis1ZjfNB0bBgKFMSvvkTtwlvBsaJq7S5wA-kzeVOVpVWwkWdVha4s38XM_pa_yr47av7-z3VTmvDRyAHcaT92whREFpLv9cj5lTeJSibyr_Mrm_YtjCZVWgaOYIhwrXwKLqPr_11inWsAkfIytvHWTxZYEcXLgAXFuUuaS3uF9gEiNQwzGTU1v0FqkqTBr4B8nW3HCN47XUu0t8Y0e-lf4s4OxQawWD79J9_5d3Ry0vbV3Am1FtGJiJvOwRsIfVChDpYStTcHTCMqtvWbV6L11BWkpzGXSW4Hv43qa-GSYOD2QU68Mb59oSk2OB-BtOLpJofmbGEGgvmwyCI9Mw","e":"AQAB","alg":"PS256","kid":"auth0_rs","use":"sig"}]}"#;
+        assert!(Auth0::new(baseline).unwrap().self_test().is_ok());
+
+        let weak = test_jwks("HS256");
+        assert!(Auth0::new(&weak).unwrap().self_test().is_err());
+    }
+}
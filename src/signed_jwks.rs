@@ -0,0 +1,130 @@
+//! Signed JWKS documents, authenticated against a pinned trust anchor before their keys are used.
+//!
+//! Serving the JWKS through a CDN, or over a network that may be intercepted, lets an attacker who
+//! controls the path substitute their own keys. With a [`JwksSignature`] configured (see
+//! [`Auth0Builder::signed_jwks`](crate::builder::Auth0Builder::signed_jwks) and
+//! [`Auth0Builder::detached_jwks_signature`](crate::builder::Auth0Builder::detached_jwks_signature)),
+//! every downloaded document must carry a valid JWS signature by a key of the trust anchor, which
+//! is provisioned out of band.
+
+use jsonwebtoken::Header;
+use crate::{expected_algorithm, Jwks, JsonWebKey, ValidationOptions};
+use crate::crypto::default_backend;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+
+/// Keys trusted to sign JWKS documents.
+#[derive(Debug, Clone)]
+pub struct JwksTrustAnchor {
+    keys: Vec<JsonWebKey>,
+}
+
+impl JwksTrustAnchor {
+    /// Trust the keys of `jwks`.
+    pub fn new(jwks: Jwks) -> JwksTrustAnchor {
+        JwksTrustAnchor { keys: jwks.keys }
+    }
+
+    /// Trust a single key.
+    pub fn from_key(key: JsonWebKey) -> JwksTrustAnchor {
+        JwksTrustAnchor { keys: vec![key] }
+    }
+
+    /// Verify `signing_input` (`header.payload`) and its base64url `signature` against the
+    /// trusted key named by `header`.
+    fn verify(&self, header: &Header, signing_input: &str, signature: &str) -> Result<(), Auth0Error> {
+        let key = match &header.kid {
+            Some(kid) => self.keys.iter().find(|key| &key.kid == kid),
+            None => return Err(untrusted("signature has no key ID (kid)")),
+        };
+        let key = key.ok_or_else(|| untrusted("signing key is not a trust anchor"))?;
+        let algorithm = expected_algorithm(key, header.alg, &ValidationOptions::default())?;
+        let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| untrusted("malformed signature"))?;
+        match default_backend().verify(algorithm, key, signing_input.as_bytes(), &signature)? {
+            true => Ok(()),
+            false => Err(untrusted("invalid signature")),
+        }
+    }
+}
+
+/// How downloaded JWKS documents are signed.
+#[derive(Debug, Clone)]
+pub enum JwksSignature {
+    /// The JWKS URL serves a compact JWS whose payload is the JWKS document.
+    Wrapped(JwksTrustAnchor),
+    /// The JWKS URL serves the plain document and `signature_url` a detached compact JWS of it
+    /// (RFC 7515 appendix F: `header..signature`).
+    Detached {
+        /// URL of the detached signature.
+        signature_url: String,
+        /// Keys trusted to sign the document.
+        anchor: JwksTrustAnchor,
+    },
+}
+
+/// Verify a compact JWS against `anchor` and parse its payload as a JWKS document.
+pub fn verify_signed_jwks(jws: &str, anchor: &JwksTrustAnchor) -> Result<Jwks, Auth0Error> {
+    let (header, payload, signature) = split_jws(jws)?;
+    let signing_input = &jws[..header.len() + 1 + payload.len()];
+    anchor.verify(&decode_header(header)?, signing_input, signature)?;
+    let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| untrusted("malformed payload"))?;
+    Jwks::parse(&String::from_utf8_lossy(&payload))
+}
+
+/// Verify the detached compact JWS `signature` of the document `jwks_str` against `anchor` and
+/// parse the document.
+pub fn verify_detached_jwks(jwks_str: &str, signature: &str, anchor: &JwksTrustAnchor) -> Result<Jwks, Auth0Error> {
+    let (header, payload, signature) = split_jws(signature.trim())?;
+    if !payload.is_empty() {
+        return Err(untrusted("detached signature carries a payload"))
+    }
+    let signing_input = format!("{}.{}", header, base64::encode_config(jwks_str, base64::URL_SAFE_NO_PAD));
+    anchor.verify(&decode_header(header)?, &signing_input, signature)?;
+    Jwks::parse(jwks_str)
+}
+
+fn split_jws(jws: &str) -> Result<(&str, &str, &str), Auth0Error> {
+    let mut parts = jws.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(payload), Some(signature), None) => Ok((header, payload, signature)),
+        _ => Err(untrusted("not a compact JWS")),
+    }
+}
+
+fn decode_header(header: &str) -> Result<Header, Auth0Error> {
+    base64::decode_config(header, base64::URL_SAFE_NO_PAD).ok()
+        .and_then(|header| serde_json::from_slice(&header).ok())
+        .ok_or_else(|| untrusted("malformed JWS header"))
+}
+
+fn untrusted(reason: &str) -> Auth0Error {
+    new_error(ErrorKind::UntrustedJwks(reason.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::test_utils::RotatingTestTenant;
+    use super::*;
+
+    #[test]
+    fn test_signed_jwks() {
+        let tenant = RotatingTestTenant::new();
+        let anchor = JwksTrustAnchor::new(tenant.v2().jwks());
+        let document = json!(tenant.v1().jwks());
+
+        let jws = tenant.v2().sign(&document);
+        assert_eq!(verify_signed_jwks(&jws, &anchor).unwrap().keys[0].kid, "v1");
+        let forged = tenant.v1().sign(&document);
+        assert!(matches!(verify_signed_jwks(&forged, &anchor).unwrap_err().kind(), ErrorKind::UntrustedJwks(_)));
+
+        let mut parts = jws.split('.');
+        let (header, payload, signature) = (parts.next().unwrap(), parts.next().unwrap(), parts.next().unwrap());
+        let body = String::from_utf8(base64::decode_config(payload, base64::URL_SAFE_NO_PAD).unwrap()).unwrap();
+        let detached = format!("{}..{}", header, signature);
+        assert_eq!(verify_detached_jwks(&body, &detached, &anchor).unwrap().keys[0].kid, "v1");
+        let tampered = body.replace("\"v1\"", "\"v3\"");
+        assert!(matches!(verify_detached_jwks(&tampered, &detached, &anchor).unwrap_err().kind(), ErrorKind::UntrustedJwks(_)));
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use serde::Serialize;
+use crate::{Jwks, JsonWebKey};
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::fetch::{response_body, Fetcher};
+use crate::options::IssuerNormalization;
+use crate::signed_jwks::{verify_detached_jwks, verify_signed_jwks, JwksSignature};
+use crate::validation::issuers_match;
+
+/// How keys are combined when both an inline JWKS and a JWKS URL are configured.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SourcePolicy {
+    /// Use the keys of both sources; a URL key replaces an inline key with the same `kid`.
+    /// Fails if the URL cannot be fetched.
+    #[default]
+    UrlOverridesInline,
+    /// Use the keys of the URL only, falling back to the inline keys if the fetch fails.
+    InlineOnFetchFailure,
+    /// Use the keys of both sources, failing with [`ErrorKind::KeySourceConflict`] if both define
+    /// the same `kid` with different key material. Fails if the URL cannot be fetched.
+    StrictMerge,
+}
+
+/// JWKS URL serving the keys of a single issuer, see
+/// [`Auth0Builder::issuer_jwks_url`](crate::builder::Auth0Builder::issuer_jwks_url).
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuerJwks {
+    /// The issuer, e.g. `https://auth.example.com/`.
+    pub issuer: String,
+    /// JWKS URL of the issuer.
+    pub url: String,
+}
+
+/// Source of verification keys other than a JWKS document, e.g.
+/// [`KmsKeyProvider`](crate::kms::KmsKeyProvider) for issuers signing with a KMS.
+///
+/// Register providers with [`Auth0Builder::key_provider`](crate::builder::Auth0Builder::key_provider);
+/// they are queried on every build and [`Auth0::refresh_keys`](crate::Auth0::refresh_keys).
+pub trait KeyProvider: Send + Sync {
+    /// Retrieve the current public keys.
+    fn keys(&self) -> Result<Vec<JsonWebKey>, Auth0Error>;
+}
+
+/// Where the keys of an [`Auth0`](crate::Auth0) instance come from, used alike by
+/// [`Auth0::from_source`](crate::Auth0::from_source), [`Auth0::set_source`](crate::Auth0::set_source)
+/// and [`Auth0Builder::source`](crate::builder::Auth0Builder::source).
+///
+/// Every source is loaded again by [`Auth0::refresh_keys`](crate::Auth0::refresh_keys); URLs
+/// and discovery documents are downloaded with the configured [`Fetcher`].
+///
+/// Example:
+/// ```
+/// use auth0_rs::{Auth0, ValidationOptions};
+/// use auth0_rs::source::JwksSource;
+/// let jwks = JwksSource::InlineStr(r#"{"keys": []}"#.to_string());
+/// let auth0 = Auth0::from_source(jwks, ValidationOptions::default()).unwrap();
+/// let tenant = JwksSource::DiscoveryIssuer("https://YOUR_TENANT.auth0.com/".to_string());
+/// ```
+pub enum JwksSource {
+    /// A JWKS document.
+    InlineStr(String),
+    /// A local JWKS file, read again on every refresh.
+    File(PathBuf),
+    /// A JWKS URL, e.g. `https://YOUR_TENANT.auth0.com/.well-known/jwks.json`.
+    Url(String),
+    /// An issuer whose JWKS URL is the `jwks_uri` of its OpenID Connect discovery document,
+    /// `{issuer}/.well-known/openid-configuration`, looked up again on every refresh.
+    ///
+    /// The document must name the same issuer.
+    DiscoveryIssuer(String),
+    /// Any other [`KeyProvider`].
+    Custom(Box<dyn KeyProvider>),
+}
+
+impl fmt::Debug for JwksSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwksSource::InlineStr(_) => f.write_str("InlineStr(..)"),
+            JwksSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            JwksSource::Url(url) => f.debug_tuple("Url").field(url).finish(),
+            JwksSource::DiscoveryIssuer(issuer) => f.debug_tuple("DiscoveryIssuer").field(issuer).finish(),
+            JwksSource::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Configured key sources of an [`Auth0`](crate::Auth0) instance, see [`Auth0Builder`](crate::builder::Auth0Builder).
+#[derive(Clone)]
+pub struct KeySources {
+    /// Inline JWKS.
+    pub inline: Option<Jwks>,
+    /// JWKS URL, e.g. `https://YOUR_TENANT.auth0.com/.well-known/jwks.json`.
+    pub url: Option<String>,
+    /// HTTP client downloading `url`.
+    pub fetcher: Option<Arc<dyn Fetcher>>,
+    /// Signature every document downloaded from `url` must carry; `None` accepts unsigned
+    /// documents.
+    pub signature: Option<JwksSignature>,
+    /// Local JWKS file, e.g. a mounted Kubernetes ConfigMap.
+    pub file: Option<PathBuf>,
+    /// Issuer whose JWKS URL is looked up in its discovery document with `fetcher`, when `url`
+    /// is not set, see [`JwksSource::DiscoveryIssuer`].
+    pub discovery_issuer: Option<String>,
+    /// JWKS URLs of single issuers, downloaded with `fetcher`; their keys are bound to the issuer.
+    pub issuer_jwks: Vec<IssuerJwks>,
+    /// Combination of the inline and URL keys. Keys of the file and of the providers are
+    /// combined with them the same way as URL keys.
+    pub policy: SourcePolicy,
+    /// Additional key providers.
+    pub providers: Vec<Arc<dyn KeyProvider>>,
+    /// Statistics of the JWKS URLs and file, shared by the clones of the sources.
+    pub(crate) stats: Arc<SourceStatsRegistry>,
+}
+
+impl KeySources {
+    /// No key source, with the default fetcher of the `blocking-http` feature if enabled.
+    pub(crate) fn empty() -> KeySources {
+        KeySources {
+            inline: None,
+            url: None,
+            #[cfg(feature = "blocking-http")]
+            fetcher: Some(Arc::new(crate::blocking::UreqFetcher::new())),
+            #[cfg(not(feature = "blocking-http"))]
+            fetcher: None,
+            signature: None,
+            file: None,
+            discovery_issuer: None,
+            issuer_jwks: vec![],
+            policy: SourcePolicy::default(),
+            providers: vec![],
+            stats: Default::default(),
+        }
+    }
+
+    /// Add `source`; an inline JWKS, a file or a URL (including a discovered one) replaces the
+    /// one configured before.
+    pub fn add(&mut self, source: JwksSource) -> Result<(), Auth0Error> {
+        match source {
+            JwksSource::InlineStr(jwks_str) => self.inline = Some(Jwks::parse(&jwks_str)?),
+            JwksSource::File(path) => self.file = Some(path),
+            JwksSource::Url(url) => {
+                self.url = Some(url);
+                self.discovery_issuer = None;
+            }
+            JwksSource::DiscoveryIssuer(issuer) => {
+                self.url = None;
+                self.discovery_issuer = Some(issuer);
+            }
+            JwksSource::Custom(provider) => self.providers.push(Arc::from(provider)),
+        }
+        Ok(())
+    }
+
+    /// Load the keys of every source and combine them according to the policy.
+    pub fn load(&self) -> Result<HashMap<String, JsonWebKey>, Auth0Error> {
+        let url_keys = match (&self.url, &self.discovery_issuer) {
+            (None, None) => None,
+            (url, issuer) => {
+                let fetcher = match &self.fetcher {
+                    Some(fetcher) => fetcher.as_ref(),
+                    None => return Err(new_error(ErrorKind::NoFetcher)),
+                };
+                Some(match (url, issuer) {
+                    (Some(url), _) => self.fetch_url_jwks(fetcher, url),
+                    (None, issuer) => self.discover_jwks_uri(fetcher, issuer.as_deref().unwrap_or_default())
+                        .and_then(|url| self.fetch_url_jwks(fetcher, &url)),
+                })
+            }
+        };
+        let inline_keys = self.inline.as_ref().map(|jwks| keymap(jwks.clone()));
+
+        let mut keys = match (inline_keys, url_keys) {
+            (None, None) if self.file.is_none() && self.providers.is_empty() && self.issuer_jwks.is_empty() => {
+                return Err(new_error(ErrorKind::NoKeySource))
+            }
+            (None, None) => HashMap::new(),
+            (Some(inline), None) => inline,
+            (None, Some(url)) => keymap(url?),
+            (Some(inline), Some(url)) => match self.policy {
+                SourcePolicy::InlineOnFetchFailure => url.map(keymap).unwrap_or(inline),
+                _ => self.merge(inline, keymap(url?))?,
+            },
+        };
+        if let Some(path) = &self.file {
+            let file_keys = keymap(self.read_jwks_file(path)?);
+            keys = self.merge(keys, file_keys)?;
+        }
+        for source in &self.issuer_jwks {
+            let fetcher = self.fetcher.as_ref().ok_or_else(|| new_error(ErrorKind::NoFetcher))?;
+            let mut issuer_keys = keymap(self.fetch_url_jwks(fetcher.as_ref(), &source.url)?);
+            for (kid, key) in issuer_keys.iter_mut() {
+                match keys.get(kid) {
+                    // the same key served for several issuers is bound to all of them
+                    Some(existing) if !existing.bound_issuers.is_empty() => {
+                        if !same_key_material(existing, key) {
+                            return Err(new_error(ErrorKind::KeySourceConflict(kid.clone())))
+                        }
+                        key.bound_issuers = existing.bound_issuers.clone();
+                    }
+                    // a key also served by an issuer-agnostic source stays usable by any issuer
+                    Some(existing) if same_key_material(existing, key) => continue,
+                    _ => {}
+                }
+                key.bound_issuers.insert(source.issuer.clone());
+            }
+            keys = self.merge(keys, issuer_keys)?;
+        }
+        for provider in &self.providers {
+            let provided = provider.keys()?.into_iter().map(|key| (key.kid.clone(), key)).collect();
+            keys = self.merge(keys, provided)?;
+        }
+        Ok(keys)
+    }
+
+    /// Statistics of every JWKS URL and file fetched so far, see [`SourceStats`].
+    pub fn source_stats(&self) -> Vec<SourceStats> {
+        self.stats.snapshot()
+    }
+
+    /// Download the JWKS document at `url`, verifying its signature if one is configured.
+    fn fetch_url_jwks(&self, fetcher: &dyn Fetcher, url: &str) -> Result<Jwks, Auth0Error> {
+        match &self.signature {
+            None => self.fetch(fetcher, url, |body| Jwks::parse(&body)),
+            Some(JwksSignature::Wrapped(anchor)) => self.fetch(fetcher, url, |body| verify_signed_jwks(body.trim(), anchor)),
+            Some(JwksSignature::Detached { signature_url, anchor }) => {
+                let body = self.fetch(fetcher, url, Ok)?;
+                // a signature not matching the document is a failure of the signature URL
+                self.fetch(fetcher, signature_url, |signature| verify_detached_jwks(&body, &signature, anchor))
+            }
+        }
+    }
+
+    /// Look up the JWKS URL of `issuer` in its discovery document.
+    fn discover_jwks_uri(&self, fetcher: &dyn Fetcher, issuer: &str) -> Result<String, Auth0Error> {
+        let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+        let invalid = |reason: &str| new_error(ErrorKind::FetchFailed { url: url.clone(), reason: reason.to_string() });
+        self.fetch(fetcher, &url, |body| {
+            let document: serde_json::Value = serde_json::from_str(&body).map_err(|_| invalid("invalid discovery document"))?;
+            match document.get("issuer").and_then(|v| v.as_str()) {
+                Some(actual) if issuers_match(issuer, actual, &IssuerNormalization::default()) => {}
+                _ => return Err(invalid("the discovery document is for another issuer")),
+            }
+            match document.get("jwks_uri").and_then(|v| v.as_str()) {
+                Some(jwks_uri) => Ok(jwks_uri.to_string()),
+                None => Err(invalid("the discovery document has no jwks_uri")),
+            }
+        })
+    }
+
+    /// Download the document at `url` and `parse` it, recording the outcome in the statistics of `url`.
+    fn fetch<T, F>(&self, fetcher: &dyn Fetcher, url: &str, parse: F) -> Result<T, Auth0Error>
+        where F: FnOnce(String) -> Result<T, Auth0Error>
+    {
+        let start = Instant::now();
+        let response = fetcher.get(url);
+        let latency = start.elapsed();
+        let status = response.as_ref().ok().map(|response| response.status);
+        let parsed = response.and_then(|response| response_body(url, response)).and_then(parse);
+        self.stats.record(url, status, latency, parsed.as_ref().err());
+        parsed
+    }
+
+    fn read_jwks_file(&self, path: &PathBuf) -> Result<Jwks, Auth0Error> {
+        let start = Instant::now();
+        let jwks = read_jwks_file(path);
+        self.stats.record(&path.display().to_string(), None, start.elapsed(), jwks.as_ref().err());
+        jwks
+    }
+
+    /// Add `other` keys to `keys`, checking conflicts under [`SourcePolicy::StrictMerge`].
+    fn merge(&self, mut keys: HashMap<String, JsonWebKey>, other: HashMap<String, JsonWebKey>) -> Result<HashMap<String, JsonWebKey>, Auth0Error> {
+        for (kid, key) in other {
+            if self.policy == SourcePolicy::StrictMerge {
+                if let Some(existing) = keys.get(&kid) {
+                    if !same_key_material(existing, &key) {
+                        return Err(new_error(ErrorKind::KeySourceConflict(kid)))
+                    }
+                }
+            }
+            keys.insert(kid, key);
+        }
+        Ok(keys)
+    }
+}
+
+/// Number of failures kept in [`SourceStats::recent_failures`].
+const FAILURE_HISTORY: usize = 10;
+
+/// Fetch statistics of a JWKS URL or file, returned by
+/// [`Auth0::source_stats`](crate::Auth0::source_stats), e.g. to alert on a flaky tenant endpoint
+/// before its keys go stale.
+///
+/// A fetch succeeds when the document is downloaded and parses into a key set, its signature
+/// verified if one is required.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SourceStats {
+    /// URL or path of the source; the detached signature of a JWKS document is a source of its own.
+    pub source: String,
+    /// Number of successful fetches.
+    pub successes: u64,
+    /// Number of failed fetches.
+    pub failures: u64,
+    /// Number of fetches failed since the last successful one.
+    pub consecutive_failures: u32,
+    /// HTTP status code of the last response, `None` for files and requests that got no response.
+    pub last_status: Option<u16>,
+    /// Duration of the last fetch.
+    pub last_latency: Duration,
+    /// Time of the last successful fetch.
+    pub last_success: Option<SystemTime>,
+    /// The last failures, oldest first, at most ten.
+    pub recent_failures: VecDeque<SourceFailure>,
+}
+
+/// A failed fetch, see [`SourceStats::recent_failures`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SourceFailure {
+    /// Time of the failure.
+    pub at: SystemTime,
+    /// HTTP status code of the response, if any.
+    pub status: Option<u16>,
+    /// Display of the error.
+    pub reason: String,
+}
+
+/// Statistics of the sources of a [`KeySources`], by source.
+#[derive(Default)]
+pub(crate) struct SourceStatsRegistry {
+    stats: Mutex<BTreeMap<String, SourceStats>>,
+}
+
+impl SourceStatsRegistry {
+    /// Record a fetch of `source` that took `latency`, failed with `error` if any.
+    fn record(&self, source: &str, status: Option<u16>, latency: Duration, error: Option<&Auth0Error>) {
+        let mut stats = self.stats.lock().unwrap();
+        let stats = stats.entry(source.to_string()).or_insert_with(|| SourceStats {
+            source: source.to_string(),
+            successes: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            last_status: None,
+            last_latency: Duration::ZERO,
+            last_success: None,
+            recent_failures: VecDeque::new(),
+        });
+        stats.last_status = status;
+        stats.last_latency = latency;
+        match error {
+            None => {
+                stats.successes += 1;
+                stats.consecutive_failures = 0;
+                stats.last_success = Some(SystemTime::now());
+            }
+            Some(error) => {
+                stats.failures += 1;
+                stats.consecutive_failures = stats.consecutive_failures.saturating_add(1);
+                if stats.recent_failures.len() == FAILURE_HISTORY {
+                    stats.recent_failures.pop_front();
+                }
+                stats.recent_failures.push_back(SourceFailure { at: SystemTime::now(), status, reason: error.to_string() });
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<SourceStats> {
+        self.stats.lock().unwrap().values().cloned().collect()
+    }
+}
+
+fn read_jwks_file(path: &PathBuf) -> Result<Jwks, Auth0Error> {
+    match std::fs::read_to_string(path) {
+        Ok(jwks_str) => Jwks::parse(&jwks_str),
+        Err(e) => Err(new_error(ErrorKind::JwksFileUnreadable { path: path.display().to_string(), reason: e.to_string() })),
+    }
+}
+
+fn keymap(jwks: Jwks) -> HashMap<String, JsonWebKey> {
+    jwks.keys.into_iter().map(|key| (key.kid.clone(), key)).collect()
+}
+
+pub(crate) fn same_key_material(a: &JsonWebKey, b: &JsonWebKey) -> bool {
+    a.kty == b.kty && a.alg == b.alg && a.n == b.n && a.e == b.e
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use crate::Auth0;
+    use crate::builder::Auth0Builder;
+    use crate::fetch::FetchResponse;
+    use crate::testing::test_jwks;
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_source_stats() {
+        let (jwks, down) = (test_jwks("RS256"), Arc::new(AtomicBool::new(false)));
+        let outage = down.clone();
+        let fetcher = move |_: &str| match outage.load(Ordering::SeqCst) {
+            true => Ok(FetchResponse { status: 503, body: String::new() }),
+            false => Ok(FetchResponse { status: 200, body: jwks.clone() }),
+        };
+        let mut auth0 = Auth0Builder::new().jwks_url("https://tenant/jwks.json").fetcher(fetcher).build().unwrap();
+        down.store(true, Ordering::SeqCst);
+        assert!(auth0.refresh_keys().is_err());
+        assert!(auth0.refresh_keys().is_err());
+
+        let stats = auth0.source_stats();
+        assert_eq!(stats.len(), 1);
+        let stats = &stats[0];
+        assert_eq!((stats.source.as_str(), stats.successes, stats.failures), ("https://tenant/jwks.json", 1, 2));
+        assert_eq!((stats.consecutive_failures, stats.last_status, stats.recent_failures.len()), (2, Some(503), 2));
+        assert!(stats.last_success.is_some());
+
+        down.store(false, Ordering::SeqCst);
+        auth0.refresh_keys().unwrap();
+        let stats = &auth0.source_stats()[0];
+        assert_eq!((stats.successes, stats.consecutive_failures, stats.last_status), (2, 0, Some(200)));
+        assert!(Auth0::new(&test_jwks("RS256")).unwrap().source_stats().is_empty());
+    }
+
+    #[test]
+    fn test_jwks_source() {
+        use crate::{Algorithm, ValidationOptions};
+        use crate::testing::{sign_token, test_claims};
+        use crate::test_utils::TestKeyPair;
+
+        struct Provider(JsonWebKey);
+
+        impl KeyProvider for Provider {
+            fn keys(&self) -> Result<Vec<JsonWebKey>, Auth0Error> {
+                Ok(vec![self.0.clone()])
+            }
+        }
+
+        let token = sign_token(Algorithm::RS256, &test_claims());
+        let mut auth0 = Auth0::from_source(JwksSource::InlineStr(test_jwks("RS256")), ValidationOptions::default()).unwrap();
+        assert!(auth0.validate_token(&token).is_ok());
+        auth0.refresh_keys().unwrap();
+
+        let rotated = TestKeyPair::new("rotated", Algorithm::RS256);
+        let path = std::env::temp_dir().join(format!("auth0_rs_jwks_source_{}.json", std::process::id()));
+        std::fs::write(&path, rotated.jwks_string()).unwrap();
+        auth0.set_source(JwksSource::File(path.clone())).unwrap();
+        assert!(auth0.validate_token(&token).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(auth0.refresh_keys().is_err());
+        assert!(auth0.set_source(JwksSource::File(path)).is_err());
+        assert!(auth0.key_map.contains_key("rotated"));
+
+        let jwks = test_jwks("RS256");
+        let fetcher = move |url: &str| Ok(FetchResponse {
+            status: 200,
+            body: match url {
+                "https://tenant/.well-known/openid-configuration" => r#"{"issuer": "https://tenant/", "jwks_uri": "https://tenant/keys"}"#.to_string(),
+                "https://other/.well-known/openid-configuration" => r#"{"issuer": "https://tenant/", "jwks_uri": "https://tenant/keys"}"#.to_string(),
+                _ => jwks.clone(),
+            },
+        });
+        let discovered = Auth0Builder::new().fetcher(fetcher.clone()).source(JwksSource::DiscoveryIssuer("https://tenant".to_string())).unwrap().build().unwrap();
+        assert!(discovered.validate_token(&token).is_ok());
+        let urls: Vec<String> = discovered.source_stats().into_iter().map(|stats| stats.source).collect();
+        assert_eq!(urls, vec!["https://tenant/.well-known/openid-configuration", "https://tenant/keys"]);
+        let err = Auth0Builder::new().fetcher(fetcher).discovery_issuer("https://other/").build().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::FetchFailed { reason, .. } if reason.contains("another issuer")));
+
+        auth0.set_source(JwksSource::Custom(Box::new(Provider(rotated.jwk())))).unwrap();
+        assert!(auth0.validate_token(&rotated.sign(&test_claims())).is_ok());
+    }
+}
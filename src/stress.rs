@@ -0,0 +1,145 @@
+//! Concurrent validation stress harness, enabled by the `stress` feature.
+//!
+//! [`run_stress`] spawns validator threads sharing one [`Auth0`] behind an [`RwLock`] while a
+//! rotator thread keeps replacing its keys with [`Auth0::update_keys`], alternating between two
+//! key sets, and checks that:
+//!
+//! - no thread panics;
+//! - no validation observes a torn key set: under the read lock, a token validates if and only if
+//!   its signing key is in [`Auth0::key_map`];
+//! - no validation, lock wait included, takes longer than [`StressConfig::max_latency`].
+//!
+//! Run a soak test with e.g. `StressConfig { duration: Duration::from_secs(600), ..Default::default() }`.
+
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::Auth0;
+use crate::source::JwksSource;
+use crate::test_utils::{RotatingTestTenant, short_lived_claims};
+
+/// Parameters of a [`run_stress`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressConfig {
+    /// Number of validator threads. Defaults to 8.
+    pub threads: usize,
+    /// Duration of the run. Defaults to 1 second.
+    pub duration: Duration,
+    /// Pause between two key updates. Defaults to 1 millisecond.
+    pub rotation_interval: Duration,
+    /// Maximum accepted latency of a single validation. Defaults to 1 second.
+    pub max_latency: Duration,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        StressConfig {
+            threads: 8,
+            duration: Duration::from_secs(1),
+            rotation_interval: Duration::from_millis(1),
+            max_latency: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of a successful [`run_stress`] run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressReport {
+    /// Number of validations performed.
+    pub validations: u64,
+    /// Number of key updates performed.
+    pub rotations: u64,
+    /// Highest observed validation latency.
+    pub max_latency: Duration,
+}
+
+/// Run the stress harness, returning a description of the first violated invariant on failure.
+pub fn run_stress(config: &StressConfig) -> Result<StressReport, String> {
+    let tenant = RotatingTestTenant::new();
+    let jwks = [
+        serde_json::to_string(&tenant.v1().jwks()).unwrap(),
+        serde_json::to_string(&tenant.v2().jwks()).unwrap(),
+    ];
+    let auth0 = Arc::new(RwLock::new(Auth0::with_options(&jwks[0], Default::default()).map_err(|e| e.to_string())?));
+    let ttl = config.duration.as_secs() + 3600;
+    let tokens = Arc::new([
+        (tenant.v1().kid().to_string(), tenant.v1().sign(&short_lived_claims(ttl))),
+        (tenant.v2().kid().to_string(), tenant.v2().sign(&short_lived_claims(ttl))),
+    ]);
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let rotator = {
+        let (auth0, stop, interval) = (auth0.clone(), stop.clone(), config.rotation_interval);
+        thread::spawn(move || -> Result<u64, String> {
+            let mut rotations = 0u64;
+            while !stop.load(Ordering::SeqCst) {
+                let next = &jwks[(rotations as usize + 1) % 2];
+                auth0.write().unwrap().load_source(JwksSource::InlineStr(next.to_string())).map_err(|e| e.to_string())?;
+                rotations += 1;
+                thread::sleep(interval);
+            }
+            Ok(rotations)
+        })
+    };
+
+    let validators: Vec<_> = (0..config.threads.max(1)).map(|index| {
+        let (auth0, stop, tokens) = (auth0.clone(), stop.clone(), tokens.clone());
+        thread::spawn(move || -> Result<(u64, Duration), String> {
+            let (mut validations, mut max_latency) = (0u64, Duration::ZERO);
+            while !stop.load(Ordering::SeqCst) {
+                let (kid, token) = &tokens[(validations as usize + index) % 2];
+                let started = Instant::now();
+                let auth0 = auth0.read().unwrap();
+                let valid = auth0.validate_token(token).is_ok();
+                max_latency = max_latency.max(started.elapsed());
+                if valid != auth0.key_map.contains_key(kid) {
+                    return Err(format!("torn read: token of key `{}` valid = {} with keys {:?}", kid, valid, auth0.key_map.keys().collect::<Vec<_>>()))
+                }
+                validations += 1;
+            }
+            Ok((validations, max_latency))
+        })
+    }).collect();
+
+    thread::sleep(config.duration);
+    stop.store(true, Ordering::SeqCst);
+
+    let mut report = StressReport { validations: 0, rotations: 0, max_latency: Duration::ZERO };
+    let mut failure = None;
+    for validator in validators {
+        match validator.join() {
+            Ok(Ok((validations, max_latency))) => {
+                report.validations += validations;
+                report.max_latency = report.max_latency.max(max_latency);
+            }
+            Ok(Err(reason)) => failure = failure.or(Some(reason)),
+            Err(_) => failure = failure.or_else(|| Some("a validator thread panicked".to_string())),
+        }
+    }
+    match rotator.join() {
+        Ok(Ok(rotations)) => report.rotations = rotations,
+        Ok(Err(reason)) => failure = failure.or(Some(reason)),
+        Err(_) => failure = failure.or_else(|| Some("the rotator thread panicked".to_string())),
+    }
+    if let Some(reason) = failure {
+        return Err(reason)
+    }
+    if report.max_latency > config.max_latency {
+        return Err(format!("validation took {:?}, above the {:?} bound", report.max_latency, config.max_latency))
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stress() {
+        let config = StressConfig { threads: 4, duration: Duration::from_millis(300), ..Default::default() };
+        let report = run_stress(&config).unwrap();
+        assert!(report.validations > 0);
+        assert!(report.rotations > 0);
+    }
+}
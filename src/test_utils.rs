@@ -0,0 +1,222 @@
+//! Test key pairs and token minting helpers, enabled by the `test-utils` feature.
+//!
+//! The embedded key pairs are public knowledge: never trust tokens signed by them in production.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Map, Value};
+use crate::error::Auth0Error;
+use crate::fetch::{FetchResponse, Fetcher};
+use crate::{JsonWebKey, Jwks};
+
+const RSA_1_PEM: &str = include_str!("../testdata/rsa_1.pem");
+pub(crate) const RSA_1_N: &str = "qBpjOqH6mDgYzT-Huw7lSytyEISGW1zirxOB5hCXZ6i2oOShCQ2E8mzVh6IdQjociMAuVUghB13R9oabUzyymQhqbSslIxBlRFk0Yts0Vj7WC96zWIFTYHWQnDfroNPPsDGA95HUHOz2XDrZWxl1J-lopwmF_QS2umZT7InYaytlzESHBFzdJe8ne62k2LELyGEvdJuP5BME0SsOYPfCldn77u5LOV_38MwwNFoQpDhjFfyG28AAGJiO5MCrAEux5ghzYxmOSmWhOmfbesAEqnpUGC2wU9Ior4Gu6-DK8nl8EgtXSOKoEDu4NjwMr_i4rZh1swUvLqqwW1rOMyORPw";
+const RSA_2_PEM: &str = include_str!("../testdata/rsa_2.pem");
+const RSA_2_N: &str = "knxif9Tvi2WCypNl1ppjAqUItV36G84cjwe9agmBaVklY4SXh6aPCUFmj28VsBgdsaSw30W2TRlMQn8eB7bRIPlrd6Xb0CEty2P4z7dUCGGOufLwOqEL_2T1ovSrbZDsQpVm6eMwUBFJ1ELsKBIgPZ8ooXNk5gxW8ddvUo-TkF8a7KyFKRcjGTWyfGSROnccPOfohoHLa7BBXdSVanqQ7qFM2bv43SkszxdHWcHg3mthNSeAAb-5usd5HONN92U6uTSHYDYYBhGxExyJeLFkmc5XcsFgu-JyC9_r3ftguSgGYuxuKL73uAkqINjM5DiiBR1o2RhR0h5uNEfATJTWEQ";
+
+/// RSA key pair able to mint tokens accepted by an [`Auth0`](crate::Auth0) instance loaded with
+/// its [`TestKeyPair::jwks`].
+///
+/// Example:
+/// ```
+/// use auth0_rs::{Algorithm, Auth0};
+/// use auth0_rs::test_utils::{TestKeyPair, short_lived_claims};
+/// let key = TestKeyPair::new("test", Algorithm::RS256);
+/// let auth0 = Auth0::from_jwks(key.jwks());
+/// assert!(auth0.validate_token(&key.sign(&short_lived_claims(60))).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestKeyPair {
+    kid: String,
+    alg: Algorithm,
+    private_pem: &'static str,
+    n: &'static str,
+}
+
+impl TestKeyPair {
+    /// Test key pair with the given key ID, signing with `alg` (an `RS*` or `PS*` algorithm).
+    pub fn new(kid: &str, alg: Algorithm) -> TestKeyPair {
+        TestKeyPair { kid: kid.to_string(), alg, private_pem: RSA_1_PEM, n: RSA_1_N }
+    }
+
+    /// Key ID of the key pair.
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// Signing algorithm of the key pair.
+    pub fn alg(&self) -> Algorithm {
+        self.alg
+    }
+
+    /// Public key as a [`JsonWebKey`].
+    pub fn jwk(&self) -> JsonWebKey {
+        JsonWebKey::new(&self.kid, &format!("{:?}", self.alg), self.n, "AQAB")
+    }
+
+    /// JWKS holding only this key.
+    pub fn jwks(&self) -> Jwks {
+        Jwks { keys: vec![self.jwk()] }
+    }
+
+    /// JWKS holding only this key, as a JSON string.
+    pub fn jwks_string(&self) -> String {
+        serde_json::to_string(&self.jwks()).unwrap()
+    }
+
+    /// Sign claims into a token whose header carries this key's `kid` and `alg`.
+    pub fn sign(&self, claims: &Value) -> String {
+        let mut header = Header::new(self.alg);
+        header.kid = Some(self.kid.clone());
+        self.sign_with_header(&header, claims)
+    }
+
+    /// Sign claims with a custom header, e.g. one with a mismatching `alg` or no `kid`.
+    pub fn sign_with_header(&self, header: &Header, claims: &Value) -> String {
+        let key = EncodingKey::from_rsa_pem(self.private_pem.as_bytes()).expect("embedded test key is valid");
+        encode(header, claims, &key).expect("test token can be signed")
+    }
+
+    /// Sign claims with a header given as JSON, e.g. one with parameters [`Header`] does not
+    /// model such as `crit`. The header is signed as is, with this key's algorithm.
+    pub fn sign_with_raw_header(&self, header: &Value, claims: &Value) -> String {
+        self.sign_payload(header, claims.to_string().as_bytes())
+    }
+
+    /// Sign an arbitrary payload with a header given as JSON, e.g. a nested token under
+    /// `cty: JWT`.
+    pub fn sign_payload(&self, header: &Value, payload: &[u8]) -> String {
+        let key = EncodingKey::from_rsa_pem(self.private_pem.as_bytes()).expect("embedded test key is valid");
+        let message = format!("{}.{}",
+            base64::encode_config(header.to_string(), base64::URL_SAFE_NO_PAD),
+            base64::encode_config(payload, base64::URL_SAFE_NO_PAD));
+        let signature = jsonwebtoken::crypto::sign(&message, &key, self.alg).expect("test token can be signed");
+        format!("{}.{}", message, signature)
+    }
+}
+
+/// Simulated tenant rotating its signing key from a `v1` to a `v2` key pair, to exercise the
+/// `NoMatchKey` → refresh → success flow deterministically.
+///
+/// Before [`rotate`](Self::rotate) tokens are signed with `v1` and the tenant publishes
+/// [`pre_rotation_jwks`](Self::pre_rotation_jwks); afterwards tokens are signed with `v2` and it
+/// publishes [`post_rotation_jwks`](Self::post_rotation_jwks), which still holds `v1` so tokens
+/// issued before the rotation keep validating. Clones share the rotation state.
+///
+/// Example:
+/// ```
+/// use auth0_rs::builder::Auth0Builder;
+/// use auth0_rs::error::ErrorKind;
+/// use auth0_rs::test_utils::{RotatingTestTenant, short_lived_claims};
+///
+/// let tenant = RotatingTestTenant::new();
+/// let mut auth0 = Auth0Builder::new()
+///     .jwks_url("https://tenant.auth0.com/.well-known/jwks.json")
+///     .fetcher(tenant.fetcher())
+///     .build()
+///     .unwrap();
+///
+/// tenant.rotate();
+/// let token = tenant.sign(&short_lived_claims(60));
+/// assert!(matches!(auth0.validate_token(&token).unwrap_err().kind(), ErrorKind::NoMatchKey));
+/// auth0.refresh_keys().unwrap();
+/// assert!(auth0.validate_token(&token).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RotatingTestTenant {
+    v1: TestKeyPair,
+    v2: TestKeyPair,
+    rotated: Arc<AtomicBool>,
+}
+
+impl Default for RotatingTestTenant {
+    fn default() -> Self {
+        RotatingTestTenant::new()
+    }
+}
+
+impl RotatingTestTenant {
+    /// Tenant signing with key `v1`, both keys using `RS256`.
+    pub fn new() -> RotatingTestTenant {
+        RotatingTestTenant::with_algorithm(Algorithm::RS256)
+    }
+
+    /// Tenant whose `v1` and `v2` keys sign with `alg` (an `RS*` or `PS*` algorithm).
+    pub fn with_algorithm(alg: Algorithm) -> RotatingTestTenant {
+        RotatingTestTenant {
+            v1: TestKeyPair::new("v1", alg),
+            v2: TestKeyPair { kid: "v2".to_string(), alg, private_pem: RSA_2_PEM, n: RSA_2_N },
+            rotated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// The pre-rotation key pair, with key ID `v1`.
+    pub fn v1(&self) -> &TestKeyPair {
+        &self.v1
+    }
+
+    /// The post-rotation key pair, with key ID `v2`.
+    pub fn v2(&self) -> &TestKeyPair {
+        &self.v2
+    }
+
+    /// Switch the signing key from `v1` to `v2` and publish the post-rotation JWKS.
+    pub fn rotate(&self) {
+        self.rotated.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`rotate`](Self::rotate) was called.
+    pub fn is_rotated(&self) -> bool {
+        self.rotated.load(Ordering::SeqCst)
+    }
+
+    /// The key pair currently signing tokens.
+    pub fn active_key(&self) -> &TestKeyPair {
+        if self.is_rotated() { &self.v2 } else { &self.v1 }
+    }
+
+    /// Sign claims with the active key pair.
+    pub fn sign(&self, claims: &Value) -> String {
+        self.active_key().sign(claims)
+    }
+
+    /// JWKS published before the rotation: `v1` only.
+    pub fn pre_rotation_jwks(&self) -> Jwks {
+        self.v1.jwks()
+    }
+
+    /// JWKS published after the rotation: `v2` followed by `v1`.
+    pub fn post_rotation_jwks(&self) -> Jwks {
+        Jwks { keys: vec![self.v2.jwk(), self.v1.jwk()] }
+    }
+
+    /// JWKS currently published by the tenant.
+    pub fn jwks(&self) -> Jwks {
+        if self.is_rotated() { self.post_rotation_jwks() } else { self.pre_rotation_jwks() }
+    }
+
+    /// [`Fetcher`] serving the currently published JWKS for any URL.
+    pub fn fetcher(&self) -> impl Fetcher + 'static {
+        let tenant = self.clone();
+        move |_url: &str| -> Result<FetchResponse, Auth0Error> {
+            Ok(FetchResponse { status: 200, body: serde_json::to_string(&tenant.jwks()).unwrap() })
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Claims with `iat` now and `exp` in `ttl_secs` seconds.
+pub fn short_lived_claims(ttl_secs: u64) -> Value {
+    let now = now();
+    let mut claims = Map::new();
+    claims.insert("sub".to_string(), json!("auth0_rs-test"));
+    claims.insert("iat".to_string(), json!(now));
+    claims.insert("exp".to_string(), json!(now + ttl_secs));
+    Value::Object(claims)
+}
@@ -0,0 +1,22 @@
+//! Shared helpers for unit tests.
+
+use jsonwebtoken::Algorithm;
+use serde_json::{json, Value};
+use crate::test_utils::TestKeyPair;
+
+pub(crate) use crate::test_utils::RSA_1_N as TEST_KEY_N;
+
+/// JWKS document holding the test key with `kid` `test` and the given `alg`.
+pub(crate) fn test_jwks(alg: &str) -> String {
+    json!({"keys": [{"kty": "RSA", "n": TEST_KEY_N, "e": "AQAB", "alg": alg, "kid": "test", "use": "sig"}]}).to_string()
+}
+
+/// Sign claims with the test key.
+pub(crate) fn sign_token(alg: Algorithm, claims: &Value) -> String {
+    TestKeyPair::new("test", alg).sign(claims)
+}
+
+/// Claims of a long-lived token for the test audience.
+pub(crate) fn test_claims() -> Value {
+    json!({"sub": "first-client", "aud": "https://github.com/digizeph/auth0_rs", "exp": 32520059430u64})
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::context::ValidationContext;
+use crate::error::Auth0Error;
+
+/// Hook consulted around validations to mitigate brute-force attempts, see
+/// [`Auth0::validate_token_throttled`](crate::Auth0::validate_token_throttled).
+///
+/// `key` is chosen by the caller, e.g. the client IP or client ID.
+pub trait ThrottlePolicy: Send + Sync {
+    /// Called before validating; returning `false` rejects the token with
+    /// [`ErrorKind::Throttled`](crate::error::ErrorKind::Throttled) without validating it.
+    fn allow(&self, key: &str) -> bool;
+
+    /// Called after a failed validation.
+    fn record_failure(&self, key: &str, error: &Auth0Error);
+
+    /// Called after a successful validation.
+    fn record_success(&self, _key: &str) {}
+
+    /// Like [`ThrottlePolicy::allow`], with the context of the validation if it was started by
+    /// [`Auth0::validate_token_with_context`](crate::Auth0::validate_token_with_context).
+    fn allow_with_context(&self, key: &str, _context: Option<&ValidationContext>) -> bool {
+        self.allow(key)
+    }
+
+    /// Like [`ThrottlePolicy::record_failure`], with the context of the validation.
+    fn record_failure_with_context(&self, key: &str, error: &Auth0Error, _context: Option<&ValidationContext>) {
+        self.record_failure(key, error)
+    }
+
+    /// Like [`ThrottlePolicy::record_success`], with the context of the validation.
+    fn record_success_with_context(&self, key: &str, _context: Option<&ValidationContext>) {
+        self.record_success(key)
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Built-in [`ThrottlePolicy`]: every failed validation consumes a token from the caller's
+/// bucket, and validations are refused while the bucket is empty.
+///
+/// Example: allow bursts of 5 failures per client, then one every 10 seconds.
+/// ```
+/// use std::time::Duration;
+/// use auth0_rs::throttle::FailureTokenBucket;
+/// let policy = FailureTokenBucket::new(5, Duration::from_secs(10));
+/// ```
+pub struct FailureTokenBucket {
+    capacity: f64,
+    refill_interval: Duration,
+    max_keys: usize,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl FailureTokenBucket {
+    /// Buckets holding `capacity` tokens, refilled by one token every `refill_interval`.
+    pub fn new(capacity: u32, refill_interval: Duration) -> Self {
+        FailureTokenBucket {
+            capacity: capacity as f64,
+            refill_interval,
+            max_keys: 100_000,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Maximum number of tracked keys; refilled buckets are dropped first when it is reached.
+    ///
+    /// Defaults to `100_000`.
+    pub fn max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        let refill = if self.refill_interval.as_secs_f64() > 0.0 {
+            elapsed / self.refill_interval.as_secs_f64()
+        } else {
+            self.capacity
+        };
+        bucket.tokens = (bucket.tokens + refill).min(self.capacity);
+        bucket.updated_at = now;
+    }
+}
+
+impl ThrottlePolicy for FailureTokenBucket {
+    fn allow(&self, key: &str) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        match buckets.get_mut(key) {
+            Some(bucket) => {
+                self.refill(bucket, Instant::now());
+                bucket.tokens >= 1.0
+            }
+            None => true,
+        }
+    }
+
+    fn record_failure(&self, key: &str, _error: &Auth0Error) {
+        let mut buckets = self.buckets.lock().unwrap();
+        let now = Instant::now();
+        if !buckets.contains_key(key) && buckets.len() >= self.max_keys {
+            for bucket in buckets.values_mut() {
+                self.refill(bucket, now);
+            }
+            let capacity = self.capacity;
+            buckets.retain(|_, bucket| bucket.tokens < capacity);
+            if buckets.len() >= self.max_keys {
+                return
+            }
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket { tokens: self.capacity, updated_at: now });
+        self.refill(bucket, now);
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{new_error, ErrorKind};
+
+    #[test]
+    fn test_failure_token_bucket() {
+        let policy = FailureTokenBucket::new(2, Duration::from_secs(3600));
+        let error = new_error(ErrorKind::InvalidToken);
+        assert!(policy.allow("10.0.0.1"));
+        policy.record_failure("10.0.0.1", &error);
+        policy.record_failure("10.0.0.1", &error);
+        assert!(!policy.allow("10.0.0.1"));
+        assert!(policy.allow("10.0.0.2"));
+
+        let policy = FailureTokenBucket::new(1, Duration::from_millis(0));
+        policy.record_failure("10.0.0.1", &error);
+        assert!(policy.allow("10.0.0.1"));
+    }
+}
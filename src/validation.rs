@@ -0,0 +1,369 @@
+//! Claim checks performed by the crate itself on top of the signature verification.
+
+use std::collections::{HashMap, HashSet};
+use serde_json::{Map, Value};
+use crate::{Claims, JsonWebKey};
+use crate::claims::{Audience, ClaimsExt, ClaimsExtract};
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::options::{EmptyKeySetPolicy, IssuerNormalization, TokenLimits, ValidationOptions};
+
+/// Check the size of the token and of its header and payload segments against `limits`.
+pub(crate) fn check_token_size(token: &str, limits: &TokenLimits) -> Result<(), Auth0Error> {
+    let too_large = |part, size, limit| Err(new_error(ErrorKind::TokenTooLarge { part, size, limit }));
+    if token.len() > limits.max_token_bytes {
+        return too_large("token", token.len(), limits.max_token_bytes)
+    }
+    let mut segments = token.splitn(3, '.');
+    let header = segments.next().unwrap_or_default();
+    if header.len() > limits.max_header_bytes {
+        return too_large("header", header.len(), limits.max_header_bytes)
+    }
+    let payload = segments.next().unwrap_or_default();
+    if payload.len() > limits.max_payload_bytes {
+        return too_large("payload", payload.len(), limits.max_payload_bytes)
+    }
+    Ok(())
+}
+
+/// Check that every segment of the token is canonical unpadded base64url, for
+/// [`ValidationOptions::strict_base64`].
+pub(crate) fn check_strict_base64(token: &str) -> Result<(), Auth0Error> {
+    for (segment, name) in token.split('.').zip(["header", "payload", "signature"].iter()) {
+        check_base64url_segment(segment, name)?;
+    }
+    Ok(())
+}
+
+fn check_base64url_segment(segment: &str, name: &'static str) -> Result<(), Auth0Error> {
+    let mut last = 0;
+    for (position, byte) in segment.bytes().enumerate() {
+        last = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            b'=' => return Err(new_error(ErrorKind::Base64Padding(name))),
+            _ => return Err(new_error(ErrorKind::Base64InvalidCharacter { segment: name, position })),
+        };
+    }
+    // The last character of a final 2 (3) character group has 4 (2) unused bits, which must be zero
+    let canonical = match segment.len() % 4 {
+        1 => false,
+        2 => last & 0b1111 == 0,
+        3 => last & 0b11 == 0,
+        _ => true,
+    };
+    if !canonical {
+        return Err(new_error(ErrorKind::Base64TrailingBits(name)))
+    }
+    Ok(())
+}
+
+/// Header parameters registered by RFC 7515 and RFC 7519, which `crit` must not list.
+const REGISTERED_HEADER_PARAMETERS: [&str; 12] = ["alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit", "zip"];
+
+/// Check the `crit` header parameter (RFC 7515 section 4.1.11) against
+/// [`ValidationOptions::critical_extensions`].
+///
+/// `crit` must be a non-empty array of distinct extension names, each present in the header and
+/// none of them a registered parameter.
+pub(crate) fn check_critical_extensions(header: &Map<String, Value>, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let critical = match header.get("crit") {
+        None => return Ok(()),
+        Some(Value::Array(critical)) if !critical.is_empty() => critical,
+        Some(_) => return Err(new_error(ErrorKind::InvalidToken)),
+    };
+    let mut seen = HashSet::new();
+    for name in critical {
+        let name = name.as_str().ok_or_else(|| new_error(ErrorKind::InvalidToken))?;
+        if !seen.insert(name) || REGISTERED_HEADER_PARAMETERS.contains(&name) || !header.contains_key(name) {
+            return Err(new_error(ErrorKind::InvalidToken))
+        }
+        if !options.critical_extensions.iter().any(|supported| supported == name) {
+            return Err(new_error(ErrorKind::UnsupportedCriticalExtension(name.to_string())))
+        }
+    }
+    Ok(())
+}
+
+/// Check the header `typ` against [`ValidationOptions::token_types`].
+pub(crate) fn check_token_type(typ: Option<&str>, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    if options.token_types.is_empty() {
+        return Ok(())
+    }
+    let normalize = |typ: &str| {
+        let typ = typ.to_ascii_lowercase();
+        typ.strip_prefix("application/").map(|typ| typ.to_string()).unwrap_or(typ)
+    };
+    match typ {
+        Some(typ) if options.token_types.iter().any(|accepted| normalize(accepted) == normalize(typ)) => Ok(()),
+        typ => Err(new_error(ErrorKind::InvalidTokenType(typ.map(|typ| typ.to_string())))),
+    }
+}
+
+/// Check the presence of [`ValidationOptions::required_claims`].
+pub(crate) fn check_required_claims(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    match options.required_claims.iter().find(|name| claims.get(name.as_str()).is_none_or(Value::is_null)) {
+        Some(name) => Err(new_error(ErrorKind::MissingClaim(name.clone()))),
+        None => Ok(()),
+    }
+}
+
+/// Check [`ValidationOptions::assertions`].
+pub(crate) fn check_assertions(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    options.assertions.iter().try_for_each(|assertion| assertion.check(claims))
+}
+
+/// Check the `azp` claim of an ID token issued to `client_id`: required when the token has
+/// several audiences, and equal to `client_id` when present.
+pub(crate) fn check_authorized_party(claims: &Claims, client_id: &str) -> Result<(), Auth0Error> {
+    let several_audiences = claims.audience().is_some_and(|aud| aud.len() > 1);
+    match claims.get("azp") {
+        None if !several_audiences => Ok(()),
+        Some(Value::String(azp)) if azp == client_id => Ok(()),
+        Some(Value::String(azp)) => Err(new_error(ErrorKind::InvalidAuthorizedParty(Some(azp.clone())))),
+        Some(azp) => Err(new_error(ErrorKind::InvalidAuthorizedParty(Some(azp.to_string())))),
+        None => Err(new_error(ErrorKind::InvalidAuthorizedParty(None))),
+    }
+}
+
+/// Check a newly loaded key set against [`ValidationOptions::empty_key_set`].
+pub(crate) fn check_key_set(keys: &HashMap<String, JsonWebKey>, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    if keys.is_empty() && options.empty_key_set == EmptyKeySetPolicy::Refuse {
+        return Err(new_error(ErrorKind::EmptyKeySet))
+    }
+    Ok(())
+}
+
+/// Check the `aud` claim against [`ValidationOptions::audience`]: the token must carry at least
+/// one of the expected audiences.
+pub(crate) fn check_audience(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let expected = match &options.audience {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    if !claims.audience().is_some_and(|aud| expected.iter().any(|e| aud.contains(e))) {
+        return Err(new_error(ErrorKind::InvalidAudience))
+    }
+    Ok(())
+}
+
+/// Check the `iss` claim against [`ValidationOptions::issuer`] and [`ValidationOptions::issuers`].
+pub(crate) fn check_issuer(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let accepted: Vec<&str> = accepted_issuers(options).collect();
+    if accepted.is_empty() || matched_issuer(claims, options).is_some() {
+        return Ok(())
+    }
+    Err(new_error(ErrorKind::InvalidIssuer {
+        expected: accepted.join("` or `"),
+        actual: claims.get("iss").and_then(|v| v.as_str()).map(|a| a.to_string()),
+    }))
+}
+
+/// [`ValidationOptions::issuer`] followed by [`ValidationOptions::issuers`].
+pub(crate) fn accepted_issuers(options: &ValidationOptions) -> impl Iterator<Item = &str> {
+    options.issuer.iter().chain(&options.issuers).map(String::as_str)
+}
+
+/// The accepted issuer matching the `iss` claim, if any.
+pub(crate) fn matched_issuer<'a>(claims: &Claims, options: &'a ValidationOptions) -> Option<&'a str> {
+    let actual = claims.get("iss").and_then(|v| v.as_str())?;
+    accepted_issuers(options).find(|expected| issuers_match(expected, actual, &options.issuer_normalization))
+}
+
+/// Check the `iss` claim against the issuers `key` is bound to, see [`JsonWebKey::issuers`].
+pub(crate) fn check_key_issuer(claims: &Claims, key: &JsonWebKey, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let bound = key.issuers();
+    if bound.is_empty() {
+        return Ok(())
+    }
+    let actual = claims.get("iss").and_then(|v| v.as_str());
+    match actual {
+        Some(actual) if bound.iter().any(|bound| issuers_match(bound, actual, &options.issuer_normalization)) => Ok(()),
+        _ => Err(new_error(ErrorKind::InvalidIssuer {
+            expected: bound.iter().map(String::as_str).collect::<Vec<_>>().join("` or `"),
+            actual: actual.map(|a| a.to_string()),
+        })),
+    }
+}
+
+/// Check the `exp` claim, which is required, allowing [`ValidationOptions::leeway`] and then
+/// [`ValidationOptions::expired_grace`] past it.
+///
+/// Returns the `exp` claim and the number of seconds it is past, leeway included, when the token
+/// is only accepted thanks to the grace period.
+pub(crate) fn check_expiry_with_grace(claims: &Claims, options: &ValidationOptions, now: u64) -> Result<Option<(u64, u64)>, Auth0Error> {
+    let exp = match claims.get("exp").and_then(|v| v.as_u64()) {
+        Some(exp) => exp,
+        None => return Err(new_error(ErrorKind::InvalidToken)),
+    };
+    let deadline = exp.saturating_add(options.leeway);
+    if now <= deadline {
+        return Ok(None)
+    }
+    let expired_for = now - deadline;
+    if expired_for > options.expired_grace.as_secs() {
+        return Err(new_error(ErrorKind::InvalidToken))
+    }
+    Ok(Some((exp, expired_for)))
+}
+
+/// Check the `nbf` claim, if any: the token is rejected until `now`, plus
+/// [`ValidationOptions::leeway`], reaches it.
+pub(crate) fn check_not_before(claims: &Claims, options: &ValidationOptions, now: u64) -> Result<(), Auth0Error> {
+    let nbf = match claims.get("nbf") {
+        Some(nbf) => nbf.as_u64().ok_or_else(|| new_error(ErrorKind::InvalidToken))?,
+        None => return Ok(()),
+    };
+    if nbf > now.saturating_add(options.leeway) {
+        return Err(new_error(ErrorKind::InvalidToken))
+    }
+    Ok(())
+}
+
+/// Check that the token carries every scope of [`ValidationOptions::required_scopes`] and of the
+/// [`ValidationOptions::audience_scopes`] entries of its audiences.
+pub(crate) fn check_scopes(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let required = required_scopes(claims, options);
+    if required.is_empty() {
+        return Ok(())
+    }
+    let scopes = token_scopes(claims);
+    for required in required {
+        if !scopes.contains(&required.as_str()) {
+            return Err(new_error(ErrorKind::MissingScope(required.clone())))
+        }
+    }
+    Ok(())
+}
+
+/// Scopes required from a token with `claims`: [`ValidationOptions::required_scopes`] followed by
+/// the [`ValidationOptions::audience_scopes`] of its audiences.
+pub(crate) fn required_scopes<'a>(claims: &Claims, options: &'a ValidationOptions) -> Vec<&'a String> {
+    let mut required: Vec<&String> = options.required_scopes.iter().collect();
+    if !options.audience_scopes.is_empty() {
+        for audience in claims.audience().iter().flat_map(Audience::iter) {
+            for scope in options.audience_scopes.get(audience).into_iter().flatten() {
+                if !required.contains(&scope) {
+                    required.push(scope);
+                }
+            }
+        }
+    }
+    required
+}
+
+/// Check the `sid` claim when [`ValidationOptions::require_session_id`] is set.
+pub(crate) fn check_session_id(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    if options.require_session_id && claims.session_id().is_none() {
+        return Err(new_error(ErrorKind::MissingSessionId))
+    }
+    Ok(())
+}
+
+/// Check the `amr` and `acr` claims against [`ValidationOptions::authentication_strength`].
+pub(crate) fn check_authentication_strength(claims: &Claims, options: &ValidationOptions) -> Result<(), Auth0Error> {
+    let strength = &options.authentication_strength;
+    let methods = claims.authentication_methods();
+    if let Some(missing) = strength.required_methods.iter().find(|method| !methods.contains(&method.as_str())) {
+        return Err(new_error(ErrorKind::InsufficientAuthentication(format!("`amr` does not list `{}`", missing))))
+    }
+    let acr = claims.authentication_context();
+    if !strength.accepts_acr(acr) {
+        let minimum = strength.minimum_acr.as_deref().unwrap_or_default();
+        return Err(new_error(ErrorKind::InsufficientAuthentication(match acr {
+            Some(acr) => format!("`acr` `{}` is weaker than `{}`", acr, minimum),
+            None => format!("no `acr`, `{}` is required", minimum),
+        })))
+    }
+    Ok(())
+}
+
+/// Insert the [`ValidationOptions::default_claims`] missing from `claims`.
+pub(crate) fn apply_default_claims(claims: &mut Claims, options: &ValidationOptions) {
+    if let Some(object) = claims.as_object_mut() {
+        for (name, value) in &options.default_claims {
+            if !object.contains_key(name) {
+                object.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Event member of the `events` claim of back-channel logout tokens.
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// Check the claims of a back-channel logout token (OIDC Back-Channel Logout 1.0, section 2.6).
+pub(crate) fn check_logout_token(claims: &Claims) -> Result<(), Auth0Error> {
+    let invalid = |reason| Err(new_error(ErrorKind::InvalidLogoutToken(reason)));
+    if claims.get("iat").and_then(|v| v.as_u64()).is_none() {
+        return invalid("iat is required")
+    }
+    if claims.get("jti").and_then(|v| v.as_str()).is_none() {
+        return invalid("jti is required")
+    }
+    let event = claims.get("events").and_then(|events| events.get(BACKCHANNEL_LOGOUT_EVENT));
+    if !event.map(|e| e.is_object()).unwrap_or(false) {
+        return invalid("events must contain the back-channel logout event")
+    }
+    let has_sub = claims.get("sub").and_then(|v| v.as_str()).is_some();
+    if !has_sub && claims.session_id().is_none() {
+        return invalid("sub or sid is required")
+    }
+    if claims.get("nonce").is_some() {
+        return invalid("nonce is forbidden")
+    }
+    Ok(())
+}
+
+/// Scopes of the token: the space-delimited `scope` claim plus the `permissions` array.
+pub(crate) fn token_scopes(claims: &Claims) -> Vec<&str> {
+    let mut scopes: Vec<&str> = match claims.get("scope").and_then(|v| v.as_str()) {
+        Some(scope) => scope.split_whitespace().collect(),
+        None => vec![],
+    };
+    if let Some(permissions) = claims.get("permissions").and_then(|v| v.as_array()) {
+        scopes.extend(permissions.iter().filter_map(|p| p.as_str()));
+    }
+    scopes
+}
+
+/// Compare two issuers after applying `normalization` to both.
+pub(crate) fn issuers_match(expected: &str, actual: &str, normalization: &IssuerNormalization) -> bool {
+    normalize_issuer(expected, normalization) == normalize_issuer(actual, normalization)
+}
+
+fn normalize_issuer(issuer: &str, normalization: &IssuerNormalization) -> String {
+    let mut issuer = issuer.to_string();
+    if normalization.trailing_slash {
+        while issuer.ends_with('/') {
+            issuer.pop();
+        }
+    }
+    if normalization.case_insensitive_host {
+        // lowercase the scheme and host, keep the path as is
+        let authority_start = issuer.find("://").map(|i| i + 3).unwrap_or(0);
+        let authority_end = issuer[authority_start..].find('/').map(|i| i + authority_start).unwrap_or(issuer.len());
+        let lowered = issuer[..authority_end].to_ascii_lowercase();
+        issuer.replace_range(..authority_end, &lowered);
+    }
+    issuer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issuer_normalization() {
+        let tolerant = IssuerNormalization::default();
+        assert!(issuers_match("https://tenant.auth0.com/", "https://tenant.auth0.com", &tolerant));
+        assert!(issuers_match("https://Tenant.Auth0.com/", "https://tenant.auth0.com/", &tolerant));
+        assert!(!issuers_match("https://tenant.auth0.com/Path", "https://tenant.auth0.com/path", &tolerant));
+
+        let strict = IssuerNormalization::strict();
+        assert!(!issuers_match("https://tenant.auth0.com/", "https://tenant.auth0.com", &strict));
+        assert!(issuers_match("https://tenant.auth0.com/", "https://tenant.auth0.com/", &strict));
+    }
+}
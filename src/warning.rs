@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+/// Non-fatal condition noticed while validating a token or loading keys.
+///
+/// Warnings are passed to the handler registered with
+/// [`Auth0::set_warning_handler`](crate::Auth0::set_warning_handler), e.g. to log them or count
+/// them in metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationWarning {
+    /// The x5c certificate of the key used to validate a token is expired.
+    CertificateExpired {
+        /// Key ID of the key.
+        kid: String,
+        /// End of the certificate's validity period, in seconds since the Unix epoch.
+        not_after: i64,
+    },
+    /// A token past its `exp` was accepted thanks to
+    /// [`ValidationOptions::expired_grace`](crate::ValidationOptions::expired_grace).
+    ExpiredWithinGrace {
+        /// Key ID of the key that signed the token.
+        kid: String,
+        /// The token's `exp` claim, in seconds since the Unix epoch.
+        exp: u64,
+        /// Seconds elapsed since `exp`, leeway included.
+        expired_for: u64,
+    },
+    /// A token was rejected because the key set is empty, see
+    /// [`EmptyKeySetPolicy::Warn`](crate::options::EmptyKeySetPolicy::Warn).
+    EmptyKeySet,
+    /// Reloading the keys after a change of the watched JWKS file failed; the previous keys stay
+    /// in use until a reload succeeds. See [`Auth0Builder::watch`](crate::builder::Auth0Builder::watch).
+    KeyReloadFailed {
+        /// Display of the load error.
+        reason: String,
+    },
+    /// The [`KeyFreshness`](crate::health::KeyFreshness) of the loaded keys changed, see
+    /// [`Auth0::key_health`](crate::Auth0::key_health).
+    KeyFreshnessChanged {
+        /// Freshness when last observed.
+        from: crate::health::KeyFreshness,
+        /// Current freshness.
+        to: crate::health::KeyFreshness,
+    },
+    /// A token was accepted while several issuers are, see
+    /// [`ValidationOptions::issuers`](crate::ValidationOptions::issuers).
+    IssuerMatched {
+        /// The accepted issuer the `iss` claim of the token matched.
+        issuer: String,
+    },
+}
+
+/// Callback receiving [`ValidationWarning`]s.
+pub type WarningHandler = Arc<dyn Fn(&ValidationWarning) + Send + Sync>;
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use crate::JsonWebKey;
+use crate::error::{new_error, ErrorKind, Auth0Error};
+use crate::events::{diff, KeyChange};
+
+/// Keys of an [`Auth0`](crate::Auth0) instance reloaded whenever its JWKS file changes.
+///
+/// The parent directory is watched rather than the file itself: Kubernetes updates ConfigMap
+/// mounts by swapping a symlinked directory, which a watch on the file would not survive. Any
+/// change in the directory only marks the keys stale; they are reloaded from every key source by
+/// the next validation, so a half-written file never replaces working keys.
+pub(crate) struct JwksWatch {
+    keys: RwLock<HashMap<String, JsonWebKey>>,
+    changed: Arc<AtomicBool>,
+    _watcher: RecommendedWatcher,
+}
+
+impl JwksWatch {
+    /// Start watching the directory of `path`, serving `keys` until it changes.
+    pub(crate) fn start(path: &Path, keys: HashMap<String, JsonWebKey>) -> Result<JwksWatch, Auth0Error> {
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = changed.clone();
+        let unreadable = |e: notify::Error| new_error(ErrorKind::JwksFileUnreadable {
+            path: path.display().to_string(),
+            reason: format!("cannot watch file: {}", e),
+        });
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if event.is_ok() {
+                flag.store(true, Ordering::SeqCst);
+            }
+        }).map_err(unreadable)?;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(directory, RecursiveMode::NonRecursive).map_err(unreadable)?;
+        Ok(JwksWatch { keys: RwLock::new(keys), changed, _watcher: watcher })
+    }
+
+    /// Reload the keys with `load`, typically [`KeySources::load`](crate::source::KeySources::load),
+    /// if the file changed since the last reload.
+    ///
+    /// Returns the resulting key changes, none if the file did not change. On failure the
+    /// previous keys are kept and the reload is retried by the next call.
+    pub(crate) fn reload_if_changed<F>(&self, load: F) -> Result<Vec<KeyChange>, Auth0Error>
+        where F: FnOnce() -> Result<HashMap<String, JsonWebKey>, Auth0Error>
+    {
+        if !self.changed.swap(false, Ordering::SeqCst) {
+            return Ok(vec![])
+        }
+        match load() {
+            Ok(keys) => Ok(self.replace(keys)),
+            Err(e) => {
+                self.changed.store(true, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    /// Replace the keys, e.g. after [`Auth0::update_keys`](crate::Auth0::update_keys), returning
+    /// the resulting key changes.
+    pub(crate) fn replace(&self, keys: HashMap<String, JsonWebKey>) -> Vec<KeyChange> {
+        let mut current = self.keys.write().unwrap();
+        let changes = diff(&current, &keys);
+        *current = keys;
+        changes
+    }
+
+    /// Mark the keys stale, as if the file changed.
+    #[cfg(test)]
+    pub(crate) fn touch(&self) {
+        self.changed.store(true, Ordering::SeqCst);
+    }
+
+    /// Current keys.
+    pub(crate) fn keys(&self) -> RwLockReadGuard<'_, HashMap<String, JsonWebKey>> {
+        self.keys.read().unwrap()
+    }
+}